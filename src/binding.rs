@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env::current_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const BINDING_FILE: &str = ".timetrack";
+
+// A project file that binds a directory (and its subdirectories) to a sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub sheet: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Binding {
+    // Looks for the binding file in the current directory and all its parents
+    pub fn find() -> Result<Option<Binding>> {
+        let dir = current_dir().context("Cannot read the current directory.")?;
+
+        for ancestor in dir.ancestors() {
+            let path = ancestor.join(BINDING_FILE);
+
+            if path.is_file() {
+                return Binding::read(&path).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn read(path: &Path) -> Result<Binding> {
+        let content = fs::read_to_string(path)?;
+
+        let mut binding: Binding = serde_json::from_str(&content)
+            .context(format!("Cannot parse the sheet binding file: {:?}", path))?;
+        binding.path = path.to_path_buf();
+
+        Ok(binding)
+    }
+
+    // Writes the binding file in the current directory
+    pub fn write(sheet: &str, tags: &[String]) -> Result<Binding> {
+        let path = current_dir()
+            .context("Cannot read the current directory.")?
+            .join(BINDING_FILE);
+
+        let binding = Binding {
+            sheet: sheet.to_string(),
+            tags: tags.to_vec(),
+            path,
+        };
+
+        fs::write(&binding.path, serde_json::to_string_pretty(&binding)?)
+            .context(format!("Cannot write the sheet binding file: {:?}", binding.path))?;
+
+        Ok(binding)
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::database::running_entries;
+use crate::State;
+
+// A small snapshot of the current sheet and the running tasks, used by the
+// shell prompt to avoid opening the database on every prompt.
+#[derive(Debug, PartialEq)]
+pub struct PromptCache {
+    pub sheet: String,
+    pub running: Vec<(String, DateTime<Local>)>,
+}
+
+impl PromptCache {
+    pub fn build(state: &State) -> Result<PromptCache> {
+        let running = running_entries(&state.database)?;
+
+        Ok(PromptCache {
+            sheet: state.current_sheet.clone(),
+            running: running.into_iter().map(|e| (e.sheet, e.start)).collect(),
+        })
+    }
+
+    // Returns None if the cache is missing, unreadable, or older than the data it mirrors
+    pub fn load(config: &Config) -> Option<PromptCache> {
+        let cache_file = cache_path(config);
+        let cache_time = modified_time(&cache_file)?;
+
+        // In WAL mode, the changes are written to the -wal file first
+        let database_file = PathBuf::from(&config.database_file);
+        let wal_file = PathBuf::from(format!("{}-wal", config.database_file));
+
+        for source in [database_file, wal_file] {
+            if modified_time(&source).is_some_and(|t| t > cache_time) {
+                return None;
+            }
+        }
+
+        PromptCache::parse(&fs::read_to_string(&cache_file).ok()?)
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        let mut content = self.sheet.clone();
+
+        for (sheet, start) in &self.running {
+            content.push_str(&format!("\n{}\t{}", sheet, start.to_rfc3339()));
+        }
+
+        fs::write(cache_path(config), content)?;
+
+        Ok(())
+    }
+
+    // The start of the running task in the given sheet, if any
+    pub fn running_start(&self, sheet: &str) -> Option<DateTime<Local>> {
+        self.running
+            .iter()
+            .find(|(s, _)| s == sheet)
+            .map(|(_, start)| *start)
+    }
+
+    fn parse(content: &str) -> Option<PromptCache> {
+        let mut lines = content.lines();
+
+        let sheet = lines.next()?.to_string();
+        let mut running = Vec::new();
+
+        for line in lines {
+            let (sheet, start) = line.split_once('\t')?;
+            let start = DateTime::parse_from_rfc3339(start).ok()?;
+
+            running.push((sheet.to_string(), start.with_timezone(&Local)));
+        }
+
+        Some(PromptCache { sheet, running })
+    }
+}
+
+fn cache_path(config: &Config) -> PathBuf {
+    PathBuf::from(&config.database_file).with_file_name("prompt.cache")
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prompt_cache() {
+        let cache = PromptCache::parse("client\nclient\t2024-03-01T10:30:00+00:00").unwrap();
+        assert_eq!(cache.sheet, "client");
+        assert!(cache.running_start("client").is_some());
+        assert!(cache.running_start("other").is_none());
+
+        let cache = PromptCache::parse("client").unwrap();
+        assert!(cache.running.is_empty());
+
+        assert!(PromptCache::parse("client\nclient\tnot a date").is_none());
+        assert!(PromptCache::parse("").is_none());
+    }
+}
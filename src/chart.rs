@@ -0,0 +1,208 @@
+use chrono::{Duration, Local};
+use clap::ValueEnum;
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::env;
+
+use crate::style::{style_string, Styles};
+use crate::utils::{duration_by_day, format_hours};
+use crate::Entry;
+
+const BAR_EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_SPARK_LEVELS: [char; 8] = ['_', '.', '-', '~', '=', '+', '*', '#'];
+
+// The longest labels and bars, so that the charts stay readable on wide terminals
+const MAX_LABEL_WIDTH: usize = 30;
+const MAX_BAR_WIDTH: usize = 50;
+
+// What each bar of a chart stands for
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ChartBy {
+    Day,
+    Sheet,
+    Note,
+}
+
+// Unicode blocks are only used on UTF-8 terminals with colors enabled,
+// otherwise the charts are drawn with plain ASCII characters.
+pub fn use_unicode() -> bool {
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return false;
+    }
+
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+// The width of the terminal, or a common one when the output is not a terminal
+pub fn terminal_width() -> usize {
+    crossterm::terminal::size()
+        .ok()
+        .map(|(columns, _)| columns as usize)
+        .filter(|columns| *columns > 0)
+        .unwrap_or(80)
+}
+
+pub fn print_chart(by: ChartBy, entries: &[Entry]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let (title, rows) = match by {
+        ChartBy::Day => ("Hours per day:", hours_per_day(entries)),
+        ChartBy::Sheet => ("Hours per sheet:", hours_per_group(entries, |e| e.sheet.clone())),
+        ChartBy::Note => ("Hours per task:", hours_per_group(entries, note_label)),
+    };
+
+    println!("{}", style_string(title, Styles::Title));
+
+    let unicode = use_unicode();
+    let label_width = rows.iter().map(|(l, _)| l.chars().count()).max().unwrap_or(0);
+    let max = rows.iter().map(|(_, d)| *d).max().unwrap_or(Duration::zero());
+
+    // The bars take the space left by the label and the hours
+    let width = terminal_width()
+        .saturating_sub(label_width + 12)
+        .clamp(10, MAX_BAR_WIDTH);
+
+    for (label, duration) in &rows {
+        println!(
+            "  {:<label_width$}  {} {}",
+            label,
+            format!("{:<width$}", bar(*duration, max, width, unicode)).green(),
+            format_hours(duration)
+        );
+    }
+}
+
+// A bar as long as the given width when the duration is the maximum one
+fn bar(duration: Duration, max: Duration, width: usize, unicode: bool) -> String {
+    if max <= Duration::zero() || duration <= Duration::zero() {
+        return String::new();
+    }
+
+    let ratio = duration.num_seconds() as f64 / max.num_seconds() as f64;
+
+    if !unicode {
+        return "#".repeat((ratio * width as f64).round().max(1.0) as usize);
+    }
+
+    let eighths = ((ratio * (width * 8) as f64).round() as usize).max(1);
+    format!("{}{}", "█".repeat(eighths / 8), BAR_EIGHTHS[eighths % 8])
+}
+
+// One character for each duration, from the lowest to the highest one
+pub fn sparkline(durations: &[Duration], unicode: bool) -> String {
+    let levels = match unicode {
+        true => SPARK_LEVELS,
+        false => ASCII_SPARK_LEVELS,
+    };
+    let max = durations.iter().max().copied().unwrap_or(Duration::zero());
+
+    durations
+        .iter()
+        .map(|d| {
+            if *d <= Duration::zero() {
+                return ' ';
+            }
+
+            let ratio = d.num_seconds() as f64 / max.num_seconds() as f64;
+            levels[((ratio * 7.0).round() as usize).min(7)]
+        })
+        .collect()
+}
+
+// The time of each day, from the first to the last one, including the empty days
+fn hours_per_day(entries: &[Entry]) -> Vec<(String, Duration)> {
+    let by_day = duration_by_day(entries);
+
+    let (Some(first), Some(last)) = (by_day.keys().next(), by_day.keys().last()) else {
+        return Vec::new();
+    };
+
+    first
+        .iter_days()
+        .take_while(|d| d <= last)
+        .map(|d| {
+            let duration = by_day.get(&d).copied().unwrap_or(Duration::zero());
+            (d.format("%a %b %d").to_string(), duration)
+        })
+        .collect()
+}
+
+// The time of each group, from the longest to the shortest
+fn hours_per_group(entries: &[Entry], key: fn(&Entry) -> String) -> Vec<(String, Duration)> {
+    let mut groups: BTreeMap<String, Duration> = BTreeMap::new();
+
+    for entry in entries {
+        let total = groups.entry(key(entry)).or_insert(Duration::zero());
+        *total = *total + entry.get_duration();
+    }
+
+    let mut rows: Vec<_> = groups.into_iter().collect();
+    rows.sort_by_key(|(_, d)| std::cmp::Reverse(*d));
+
+    rows
+}
+
+fn note_label(entry: &Entry) -> String {
+    match entry.name.trim() {
+        "" => "(no description)".to_string(),
+        name if name.chars().count() > MAX_LABEL_WIDTH => {
+            let short: String = name.chars().take(MAX_LABEL_WIDTH - 1).collect();
+            format!("{}…", short)
+        }
+        name => name.to_string(),
+    }
+}
+
+// The time of each of the last days, ending today
+pub fn last_days(entries: &[Entry], days: i64) -> Vec<Duration> {
+    let by_day = duration_by_day(entries);
+    let today = Local::now().date_naive();
+
+    (0..days)
+        .rev()
+        .map(|n| {
+            let day = today - Duration::days(n);
+            by_day.get(&day).copied().unwrap_or(Duration::zero())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        let max = Duration::hours(8);
+
+        assert_eq!(bar(Duration::hours(8), max, 4, true), "████");
+        assert_eq!(bar(Duration::hours(1), max, 4, true), "▌");
+        assert_eq!(bar(Duration::hours(4), max, 4, false), "##");
+        assert_eq!(bar(Duration::minutes(1), max, 4, false), "#");
+        assert_eq!(bar(Duration::zero(), max, 4, true), "");
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let durations = [
+            Duration::zero(),
+            Duration::hours(1),
+            Duration::hours(4),
+            Duration::hours(8),
+        ];
+
+        assert_eq!(sparkline(&durations, true), " ▂▅█");
+        assert_eq!(sparkline(&durations, false), " .=#");
+        assert_eq!(sparkline(&[Duration::zero(); 3], true), "   ");
+    }
+}
@@ -0,0 +1,238 @@
+use chrono::{DateTime, Local};
+use langtime::parse;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::commands::timer::{ensure_writable, start_entry, stop_entry, Refusal};
+use crate::config::Config;
+use crate::database::{
+    get_all_entries, get_all_sheets, get_archived_sheets, get_entry_by_id, get_sheet_entries,
+    remove_entry_by_id, running_entries, running_entry, update_entry,
+};
+use crate::hooks::{Hook, PendingHook};
+use crate::range::DateRange;
+use crate::State;
+
+// The operations shared by the JSON API and the JSON-RPC interface.
+// Errors carry an HTTP status, which the JSON-RPC interface turns into an error code.
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: u16, message: &str) -> ApiError {
+        ApiError {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+// The rules and the pre-hooks refuse with a conflict. Anything unexpected, like a
+// database error, is a server error.
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> ApiError {
+        match err.downcast_ref::<Refusal>() {
+            Some(refusal) => ApiError::new(409, &refusal.to_string()),
+            None => ApiError::new(500, &format!("{:#}", err)),
+        }
+    }
+}
+
+pub type Reply = std::result::Result<Value, ApiError>;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct InParams {
+    pub name: String,
+    pub sheet: Option<String>,
+    pub at: Option<String>,
+    pub tags: Vec<String>,
+    pub switch: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct OutParams {
+    pub sheet: Option<String>,
+    pub at: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct EntryPatch {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub name: Option<String>,
+    pub sheet: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+// Every sheet is included by default. The range is anything like "last week".
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct EntryQuery {
+    pub sheet: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub range: Option<String>,
+}
+
+pub fn list_entries(query: EntryQuery, config: &Config, state: &State) -> Reply {
+    let mut start = query.start.as_deref().map(parse_time).transpose()?;
+    let mut end = query.end.as_deref().map(parse_time).transpose()?;
+
+    if let Some(range) = &query.range {
+        let range = DateRange::parse(range, config.week_start)
+            .map_err(|e| ApiError::new(400, &format!("{:#}", e)))?;
+        start = Some(range.start);
+        end = Some(range.end);
+    }
+
+    let mut entries = match &query.sheet {
+        Some(sheet) => get_sheet_entries(sheet, &state.database)?,
+        None => get_all_entries(&state.database)?,
+    };
+
+    entries.retain(|e| {
+        start.is_none_or(|start| e.start >= start) && end.is_none_or(|end| e.start <= end)
+    });
+    entries.sort_by_key(|e| e.start);
+
+    Ok(json!(entries))
+}
+
+pub fn patch_entry(id: usize, patch: EntryPatch, state: &mut State) -> Reply {
+    let Some(mut entry) = get_entry_by_id(&id, &state.database)? else {
+        return Err(ApiError::new(404, &format!("Entry not found. Id: {}", id)));
+    };
+
+    // Both the sheet the task is in and the one it is moved to must be writable
+    ensure_writable(&entry.sheet, false, state)?;
+
+    if let Some(sheet) = &patch.sheet {
+        ensure_writable(sheet, false, state)?;
+        entry.sheet = sheet.to_string();
+    }
+
+    if let Some(start) = &patch.start {
+        entry.start = parse_time(start)?;
+    }
+
+    if let Some(end) = &patch.end {
+        entry.end = Some(parse_time(end)?);
+    }
+
+    if let Some(name) = patch.name {
+        entry.name = name;
+    }
+
+    if let Some(tags) = patch.tags {
+        entry.tags = tags;
+    }
+
+    update_entry(&entry, &state.database).map_err(conflict)?;
+
+    state.pending_hooks.push(PendingHook {
+        hook: Hook::PostEdit,
+        sheet: entry.sheet.clone(),
+        entry: Some(entry.clone()),
+    });
+
+    Ok(json!(entry))
+}
+
+pub fn delete_entry(id: usize, state: &State) -> Reply {
+    let Some(entry) = get_entry_by_id(&id, &state.database)? else {
+        return Err(ApiError::new(404, &format!("Entry not found. Id: {}", id)));
+    };
+
+    ensure_writable(&entry.sheet, false, state)?;
+    remove_entry_by_id(&id, &state.database)?;
+
+    Ok(json!(entry))
+}
+
+// Like `t in`, including --switch to stop the running task first
+pub fn check_in(params: InParams, config: &Config, state: &mut State) -> Reply {
+    let sheet = params.sheet.as_deref().unwrap_or(state.active_sheet()).to_string();
+    let start = match &params.at {
+        Some(at) => parse_time(at)?,
+        None => Local::now(),
+    };
+
+    let changes = start_entry(
+        &params.name,
+        &sheet,
+        start,
+        params.switch,
+        &params.tags,
+        false,
+        config,
+        state.hook_output,
+        state,
+    )?;
+    state.pending_hooks.extend(changes.hooks);
+
+    Ok(json!(changes.entry))
+}
+
+pub fn check_out(params: OutParams, config: &Config, state: &mut State) -> Reply {
+    let sheet = params.sheet.as_deref().unwrap_or(state.active_sheet()).to_string();
+    let end = match &params.at {
+        Some(at) => parse_time(at)?,
+        None => Local::now(),
+    };
+
+    let changes = stop_entry(&sheet, end, false, config, state.hook_output, state)?;
+    state.pending_hooks.extend(changes.hooks);
+
+    Ok(json!(changes.entry))
+}
+
+pub fn list_sheets(state: &State) -> Reply {
+    let archived = get_archived_sheets(&state.database)?;
+    let running: Vec<String> = running_entries(&state.database)?
+        .into_iter()
+        .map(|e| e.sheet)
+        .collect();
+
+    let sheets: Vec<Value> = get_all_sheets(&state.database)?
+        .iter()
+        .map(|sheet| {
+            json!({
+                "name": sheet,
+                "current": sheet == state.active_sheet(),
+                "archived": archived.contains(sheet),
+                "running": running.contains(sheet),
+            })
+        })
+        .collect();
+
+    Ok(json!(sheets))
+}
+
+pub fn current(state: &State) -> Reply {
+    let sheet = state.active_sheet();
+    let entry = running_entry(&state.database, sheet)?;
+
+    Ok(json!({ "sheet": sheet, "entry": entry }))
+}
+
+// A sheet can only have one running task, which the database enforces
+fn conflict(err: anyhow::Error) -> ApiError {
+    match err.to_string().contains("UNIQUE constraint failed") {
+        true => ApiError::new(409, "The sheet already has a running task."),
+        false => err.into(),
+    }
+}
+
+// RFC 3339 timestamps, or anything that the commands accept, like "15 minutes ago"
+fn parse_time(s: &str) -> std::result::Result<DateTime<Local>, ApiError> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Local));
+    }
+
+    parse(s).map_err(|_| ApiError::new(400, &format!("Invalid time: {}", s)))
+}
@@ -0,0 +1,248 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Local};
+use rusqlite::{Connection, DatabaseName};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use tabled::builder::Builder;
+use tabled::settings::object::Rows;
+use tabled::settings::themes::Colorization;
+use tabled::settings::{Color, Style};
+
+use crate::config::Config;
+use crate::database::integrity_check;
+use crate::style::{style_string, Styles};
+use crate::State;
+
+// Only the automatic snapshots are rotated. The ones taken on request, or before a
+// restore or a repair, are kept until they are removed by hand.
+const ROTATED_REASONS: [&str; 2] = ["daily", "before-delete"];
+
+pub fn backup_database(path: Option<&String>, config: &Config, state: &State) -> Result<()> {
+    let path = match path {
+        Some(path) => {
+            let path = PathBuf::from(path);
+
+            if path.exists() {
+                return Err(anyhow!("The file already exists: {:?}", path));
+            }
+
+            state
+                .database
+                .backup(DatabaseName::Main, &path, None)
+                .context(format!("Cannot write the backup to {:?}", path))?;
+
+            path
+        }
+        None => take_snapshot("manual", config, state)?,
+    };
+
+    println!(
+        "{} {}",
+        style_string("Database saved to:", Styles::Message),
+        path.display()
+    );
+
+    Ok(())
+}
+
+pub fn restore_database(path: &str, config: &Config, state: &mut State) -> Result<()> {
+    let path = PathBuf::from(path);
+
+    verify_database(&path)?;
+
+    // The current data is kept, in case the wrong backup was restored
+    let snapshot = take_snapshot("before-restore", config, state)?;
+
+    state
+        .database
+        .restore(DatabaseName::Main, &path, None::<fn(_)>)
+        .context(format!("Cannot restore the backup from {:?}", path))?;
+
+    println!(
+        "{} {}",
+        style_string("Database restored from:", Styles::Message),
+        path.display()
+    );
+    println!(
+        "{} {}",
+        style_string("The previous data was saved to:", Styles::Message),
+        snapshot.display()
+    );
+
+    Ok(())
+}
+
+pub fn list_snapshots(config: &Config) -> Result<()> {
+    let snapshots = get_snapshots(config)?;
+
+    if snapshots.is_empty() {
+        println!("{}", style_string("There are no snapshots.", Styles::Message));
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        style_string("Snapshots in:", Styles::Title),
+        snapshots_dir(config).display()
+    );
+
+    let mut builder = Builder::new();
+    builder.push_record(vec!["Date", "Reason", "Size", "File"]);
+
+    for snapshot in snapshots.iter().rev() {
+        let name = snapshot.file_name().unwrap().to_string_lossy().to_string();
+        let metadata = fs::metadata(snapshot)?;
+        let date: DateTime<Local> = metadata.modified()?.into();
+
+        builder.push_record(vec![
+            date.format("%a %b %d, %Y %H:%M:%S").to_string(),
+            snapshot_reason(snapshot),
+            format!("{} KB", metadata.len().div_ceil(1024)),
+            name,
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.with(Colorization::exact([Color::BOLD], Rows::first()));
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+// Takes the automatic snapshots, if they are enabled: one before each destructive
+// command, and one every day.
+pub fn auto_snapshot(destructive: bool, config: &Config, state: &State) -> Result<()> {
+    if !config.auto_snapshots {
+        return Ok(());
+    }
+
+    if destructive {
+        take_snapshot("before-delete", config, state)?;
+        return Ok(());
+    }
+
+    let last_snapshot = get_snapshots(config)?
+        .iter()
+        .rfind(|s| snapshot_reason(s) == "daily")
+        .and_then(|s| fs::metadata(s).and_then(|m| m.modified()).ok())
+        .map(DateTime::<Local>::from);
+
+    let is_due = match last_snapshot {
+        Some(date) => Local::now() - date > Duration::days(1),
+        None => true,
+    };
+
+    if is_due {
+        take_snapshot("daily", config, state)?;
+    }
+
+    Ok(())
+}
+
+pub fn take_snapshot(reason: &str, config: &Config, state: &State) -> Result<PathBuf> {
+    let dir = snapshots_dir(config);
+    fs::create_dir_all(&dir).context(format!(
+        "Cannot create the snapshots folder. The expected path was: {:?}",
+        dir
+    ))?;
+
+    let name = format!("{}_{}.db", Local::now().format("%Y-%m-%d_%H-%M-%S-%3f"), reason);
+    let path = dir.join(name);
+
+    state
+        .database
+        .backup(DatabaseName::Main, &path, None)
+        .context(format!("Cannot write the snapshot to {:?}", path))?;
+
+    rotate_snapshots(config)?;
+
+    Ok(path)
+}
+
+// Removes the oldest automatic snapshots, keeping only the configured amount
+fn rotate_snapshots(config: &Config) -> Result<()> {
+    let mut snapshots = get_snapshots(config)?;
+    snapshots.retain(|s| ROTATED_REASONS.contains(&snapshot_reason(s).as_str()));
+
+    let excess = snapshots.len().saturating_sub(config.snapshot_retention);
+
+    for snapshot in snapshots.iter().take(excess) {
+        fs::remove_file(snapshot)?;
+    }
+
+    Ok(())
+}
+
+// The snapshots sorted from the oldest to the newest
+fn get_snapshots(config: &Config) -> Result<Vec<PathBuf>> {
+    let dir = snapshots_dir(config);
+
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "db"))
+        .collect();
+
+    // The names start with the date, so they are sorted chronologically
+    snapshots.sort();
+
+    Ok(snapshots)
+}
+
+// The file name is made of the date, the time and the reason
+fn snapshot_reason(snapshot: &Path) -> String {
+    let name = snapshot.file_stem().unwrap_or_default().to_string_lossy();
+
+    name.splitn(3, '_').nth(2).unwrap_or("").to_string()
+}
+
+fn snapshots_dir(config: &Config) -> PathBuf {
+    PathBuf::from(&config.database_file).with_file_name("snapshots")
+}
+
+// Makes sure that a file is a healthy timetrack database before restoring it
+fn verify_database(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        return Err(anyhow!("The backup file does not exist: {:?}", path));
+    }
+
+    // Checking the full-text index needs to write, so the checks run on a copy,
+    // leaving the backup untouched
+    let copy = env::temp_dir().join(format!("timetrack-verify-{}.db", process::id()));
+    fs::copy(path, &copy).context(format!("Cannot read the backup file: {:?}", path))?;
+
+    let res = check_database(&copy);
+    let _ = fs::remove_file(&copy);
+
+    res
+}
+
+fn check_database(path: &Path) -> Result<()> {
+    let db = Connection::open(path).context(format!("Cannot open the backup file: {:?}", path))?;
+
+    let integrity = integrity_check(&db).context("The backup file is not a valid database.")?;
+
+    if integrity != ["ok"] {
+        return Err(anyhow!("The backup file is corrupted: {}", integrity.join(", ")));
+    }
+
+    let has_entries: bool = db.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'entries')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    if !has_entries {
+        return Err(anyhow!("The backup file is not a timetrack database."));
+    }
+
+    Ok(())
+}
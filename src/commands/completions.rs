@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Result};
+use clap_complete::env::Shells;
+use clap_complete::{CompletionCandidate, Shell};
+use std::cmp::Reverse;
+use std::io::stdout;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::database::{get_all_entries, get_all_sheets, get_archived_sheets};
+use crate::State;
+
+const BIN_NAME: &str = "t";
+
+// The environment variable that asks the program for the completions, see `CompleteEnv`
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+// How many entries or notes are suggested by the dynamic completions
+const RECENT_LIMIT: usize = 20;
+
+// The script only registers the completions: the shell asks the program for them every time
+pub fn print_completions(shell: Shell) -> Result<()> {
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(&shell.to_string()) else {
+        return Err(anyhow!("The completions are not available for {}", shell));
+    };
+
+    completer.write_registration(COMPLETE_VAR, BIN_NAME, BIN_NAME, BIN_NAME, &mut stdout())?;
+
+    Ok(())
+}
+
+// The completions are asked while typing, so the errors are not reported
+fn completion_state() -> Option<State> {
+    let config = Config::build().ok()?;
+
+    // Completing must not create the database
+    if !Path::new(&config.database_file).exists() {
+        return None;
+    }
+
+    State::build(&config).ok()
+}
+
+pub fn complete_sheets() -> Vec<CompletionCandidate> {
+    let Some(state) = completion_state() else {
+        return Vec::new();
+    };
+
+    let mut sheets = get_all_sheets(&state.database).unwrap_or_default();
+    let archived = get_archived_sheets(&state.database).unwrap_or_default();
+    sheets.retain(|s| !archived.contains(s));
+
+    if !sheets.contains(&state.current_sheet) {
+        sheets.push(state.current_sheet.clone());
+    }
+
+    sheets.sort();
+
+    sheets.into_iter().map(CompletionCandidate::new).collect()
+}
+
+pub fn complete_ids() -> Vec<CompletionCandidate> {
+    let Some(state) = completion_state() else {
+        return Vec::new();
+    };
+
+    let mut entries = get_all_entries(&state.database).unwrap_or_default();
+    entries.sort_by_key(|e| Reverse(e.start));
+
+    entries
+        .iter()
+        .take(RECENT_LIMIT)
+        .enumerate()
+        .map(|(i, entry)| {
+            CompletionCandidate::new(entry.id.unwrap().to_string())
+                .help(Some(entry.name.clone().into()))
+                .display_order(Some(i))
+        })
+        .collect()
+}
+
+pub fn complete_notes() -> Vec<CompletionCandidate> {
+    let Some(state) = completion_state() else {
+        return Vec::new();
+    };
+
+    let mut entries = get_all_entries(&state.database).unwrap_or_default();
+    entries.sort_by_key(|e| Reverse(e.start));
+
+    let mut notes: Vec<&String> = Vec::new();
+
+    for entry in &entries {
+        if !entry.name.is_empty() && !notes.contains(&&entry.name) {
+            notes.push(&entry.name);
+        }
+    }
+
+    notes
+        .iter()
+        .take(RECENT_LIMIT)
+        .map(|note| CompletionCandidate::new(note.as_str()))
+        .collect()
+}
@@ -1,40 +1,164 @@
 use anyhow::Result;
+use chrono::{Duration, Local, NaiveTime};
+use std::io::{stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration as StdDuration;
 
-use crate::database::running_entries;
+use crate::config::Config;
+use crate::database::{get_entries_since, running_entries};
 use crate::style::{style_string, Styles};
-use crate::utils::{format_duration, time_from_now};
+use crate::utils::{format_duration, local_datetime, time_from_now};
+use crate::Entry;
 use crate::State;
 
-pub fn current_task(state: &State) -> Result<()> {
+pub fn current_task(watch: &bool, config: &Config, state: &State) -> Result<()> {
+    if *watch {
+        return watch_current_task(config, state);
+    }
+
+    for line in current_task_lines(state)? {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+fn current_task_lines(state: &State) -> Result<Vec<String>> {
     let mut entries = running_entries(&state.database)?;
 
     entries.sort_unstable();
 
-    println!(
-        "{}{}",
+    let source = match &state.binding {
+        Some(binding) => format!("bound by {}", binding.path.display()),
+        None => "global".to_string(),
+    };
+
+    let mut lines = vec![format!(
+        "{}{} ({})",
         style_string("Currently on sheet: ", Styles::Title),
-        style_string(&state.current_sheet, Styles::Primary)
-    );
+        style_string(state.active_sheet(), Styles::Primary),
+        source
+    )];
 
     // Early return if there is no active task
     if entries.is_empty() {
-        println!(
+        lines.push(format!(
             "{}",
             style_string("There is no active task.", Styles::Message)
-        );
+        ));
 
-        return Ok(());
+        return Ok(lines);
     }
 
     // There will always be only one active entry for each sheet
-    println!("{}", style_string("Active tasks:", Styles::Title));
+    lines.push(format!("{}", style_string("Active tasks:", Styles::Title)));
     for entry in &entries {
-        println!(
+        lines.push(format!(
             "{}: {} ({})",
             style_string(&entry.sheet, Styles::Primary),
-            style_string(&entry.name, Styles::Secondary),
+            style_string(&entry.description(), Styles::Secondary),
             format_duration(&time_from_now(&entry.start))
-        );
+        ));
+    }
+
+    Ok(lines)
+}
+
+fn today_lines(config: &Config, state: &State) -> Result<Vec<String>> {
+    let today = Local::now().date_naive();
+    let day_start = local_datetime(today.and_time(NaiveTime::MIN));
+    let entries = get_entries_since(day_start, &state.database)?;
+
+    let today_entries = entries.iter();
+
+    // A task started yesterday only counts from midnight
+    let today_duration = |e: &Entry| e.end.unwrap_or(Local::now()) - e.start.max(day_start);
+
+    let sheet_total: Duration = today_entries
+        .clone()
+        .filter(|e| e.sheet == state.active_sheet())
+        .map(today_duration)
+        .sum();
+
+    let mut lines = vec![format!(
+        "{} {}",
+        style_string("Today on this sheet:", Styles::Title),
+        format_duration(&sheet_total)
+    )];
+
+    if let Some(target) = config.daily_target {
+        let total: Duration = today_entries.map(today_duration).sum();
+        let ratio = total.num_seconds() as f64 / target.num_seconds() as f64;
+
+        lines.push(format!(
+            "{} {} / {} {} {:.0}%",
+            style_string("Daily target:", Styles::Title),
+            format_duration(&total),
+            format_duration(&target),
+            progress_bar(ratio, 20),
+            ratio * 100.0
+        ));
+    }
+
+    Ok(lines)
+}
+
+fn progress_bar(ratio: f64, width: usize) -> String {
+    let filled = ((ratio.clamp(0.0, 1.0) * width as f64).round()) as usize;
+
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+fn watch_current_task(config: &Config, state: &State) -> Result<()> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = running.clone();
+
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))?;
+
+    let mut out = stdout();
+
+    // Hide the cursor while redrawing, and make sure it's restored even on errors
+    write!(out, "\x1b[?25l")?;
+    let res = redraw_until_stopped(&running, config, state);
+    write!(out, "\x1b[?25h")?;
+    out.flush()?;
+
+    res
+}
+
+fn redraw_until_stopped(running: &AtomicBool, config: &Config, state: &State) -> Result<()> {
+    let mut out = stdout();
+    let mut printed_lines = 0;
+
+    while running.load(Ordering::SeqCst) {
+        // The entries are queried on every tick, since another process
+        // could have changed them in the meantime
+        let mut lines = current_task_lines(state)?;
+        lines.push("".to_string());
+        lines.extend(today_lines(config, state)?);
+
+        // Move back to the first line of the previous frame and clear it
+        if printed_lines > 0 {
+            write!(out, "\x1b[{}A", printed_lines)?;
+        }
+        write!(out, "\r\x1b[J")?;
+
+        for line in &lines {
+            writeln!(out, "{}", line)?;
+        }
+        out.flush()?;
+
+        printed_lines = lines.len();
+
+        // Sleep in small steps, so that Ctrl-C is handled quickly
+        for _ in 0..10 {
+            if !running.load(Ordering::SeqCst) {
+                break;
+            }
+            sleep(StdDuration::from_millis(100));
+        }
     }
 
     Ok(())
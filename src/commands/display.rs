@@ -6,7 +6,8 @@ use tabled::settings::object::Rows;
 use tabled::settings::themes::Colorization;
 use tabled::settings::{Border, Color, Padding, Style};
 
-use crate::database::get_sheet_entries;
+use crate::chart::{print_chart, ChartBy};
+use crate::database::{get_all_sheets, get_archived_sheets, get_sheet_entries};
 use crate::style::{style_string, Styles};
 use crate::utils::{day_begin, day_end, format_duration, is_same_day};
 use crate::Entry;
@@ -18,7 +19,7 @@ pub struct ReadableOptions {
     pub show_partial_sum: bool,
     pub show_total: bool,
     pub show_headings: bool,
-    pub padding: usize,
+    pub chart: Option<ChartBy>,
 }
 
 impl ReadableOptions {
@@ -29,7 +30,7 @@ impl ReadableOptions {
             show_partial_sum: false,
             show_total: false,
             show_headings: false,
-            padding: 0,
+            chart: None,
         }
     }
 
@@ -40,37 +41,57 @@ impl ReadableOptions {
             show_partial_sum: true,
             show_total: true,
             show_headings: true,
-            padding: 0,
+            chart: None,
         }
     }
 }
 
+// The sheets shown by the display command
+pub enum SheetFilter<'a> {
+    Sheet(Option<&'a String>),
+    All { archived: bool },
+}
+
 pub fn display_tasks(
     print_json: &bool,
-    sheet: Option<&String>,
+    sheets: SheetFilter,
     start: Option<DateTime<Local>>,
     end: Option<DateTime<Local>>,
     filter_by_date: &bool,
-    ids: &bool,
+    options: &ReadableOptions,
     state: &State,
 ) -> Result<()> {
     // Getting the data
-    let sheet = sheet.unwrap_or(&state.current_sheet);
-    let mut entries = get_sheet_entries(sheet, &state.database)?;
-
-    if entries.is_empty() {
-        println!(
-            "{} {}",
-            style_string("No sheet found with name:", Styles::Message),
-            sheet
-        );
-        return Ok(());
-    }
+    let show_all = matches!(sheets, SheetFilter::All { .. });
+
+    let sheets = match sheets {
+        SheetFilter::Sheet(sheet) => {
+            let sheet = sheet.map(|s| s.as_str()).unwrap_or(state.active_sheet());
+
+            if get_sheet_entries(sheet, &state.database)?.is_empty() {
+                println!(
+                    "{} {}",
+                    style_string("No sheet found with name:", Styles::Message),
+                    sheet
+                );
+                return Ok(());
+            }
+
+            vec![sheet.to_string()]
+        }
+        SheetFilter::All { archived } => {
+            let mut sheets = get_all_sheets(&state.database)?;
 
-    // Sorting
-    entries.sort_by(|a, b| a.start.cmp(&b.start));
+            if !archived {
+                let archived_sheets = get_archived_sheets(&state.database)?;
+                sheets.retain(|s| !archived_sheets.contains(s));
+            }
+
+            sheets.sort();
+            sheets
+        }
+    };
 
-    // Filtering
     let mut start = start;
     let mut end = end;
 
@@ -79,27 +100,44 @@ pub fn display_tasks(
         end = end.map(day_end);
     }
 
-    entries.retain(|e| {
-        if start.is_some() && e.start < start.unwrap() {
-            return false;
+    let mut all_entries = Vec::new();
+
+    for sheet in &sheets {
+        let mut entries = get_sheet_entries(sheet, &state.database)?;
+
+        // Sorting
+        entries.sort_by_key(|e| e.start);
+
+        // Filtering
+        entries.retain(|e| {
+            if start.is_some() && e.start < start.unwrap() {
+                return false;
+            }
+
+            if end.is_some() && e.start > end.unwrap() {
+                return false;
+            }
+
+            true
+        });
+
+        // When showing every sheet, the ones without matching tasks are skipped
+        if entries.is_empty() && show_all {
+            continue;
         }
 
-        if end.is_some() && e.start > end.unwrap() {
-            return false;
+        if !print_json {
+            print_all_tasks_readable(sheet, &entries, options);
         }
 
-        true
-    });
+        all_entries.append(&mut entries);
+    }
 
     // Displaying
-    match print_json {
-        true => print_all_tasks_json(&entries)?,
-        false => {
-            let mut options = ReadableOptions::complete();
-            options.show_ids = *ids;
-
-            print_all_tasks_readable(sheet, &entries, &options);
-        }
+    match (print_json, options.chart) {
+        (true, _) => print_all_tasks_json(&all_entries)?,
+        (false, Some(by)) => print_chart(by, &all_entries),
+        (false, None) => (),
     };
 
     Ok(())
@@ -167,7 +205,7 @@ pub fn print_all_tasks_readable(sheet: &str, entries: &Vec<Entry>, options: &Rea
             &start,
             &end,
             &format_duration(&entry.get_duration()),
-            &entry.name,
+            &entry.description(),
         ]);
     }
 
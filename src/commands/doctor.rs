@@ -0,0 +1,342 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use std::collections::HashMap;
+
+use crate::commands::backup::take_snapshot;
+use crate::config::Config;
+use crate::database::{
+    create_tables, get_entries_to_check, get_entry_timestamps, get_sheets_to_check,
+    integrity_check, remove_entry_by_id, update_entry, update_entry_timestamp,
+};
+use crate::style::{style_string, Styles};
+use crate::utils::{confirm_action, str_to_datetime};
+use crate::{Entry, State};
+
+// Other formats that a timestamp could have been saved with, tried when the
+// standard one can't be parsed. They are all read as UTC, like the standard one.
+const LENIENT_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+
+enum Problem {
+    BrokenTimestamp {
+        id: usize,
+        column: &'static str,
+        value: String,
+        repaired: Option<DateTime<Local>>,
+    },
+    EndBeforeStart(Entry),
+    MultipleRunning {
+        sheet: String,
+        entries: Vec<Entry>,
+    },
+    EmptyCurrentSheet {
+        sheet: String,
+        replacement: String,
+    },
+}
+
+impl Problem {
+    fn describe(&self) -> String {
+        match self {
+            Problem::BrokenTimestamp {
+                id, column, value, ..
+            } => format!(
+                "Entry {} has an unreadable {} time: {:?}",
+                id, column, value
+            ),
+            Problem::EndBeforeStart(entry) => format!(
+                "Entry {} ends before it starts ({} -> {})",
+                entry.id.unwrap(),
+                entry.start.format("%Y-%m-%d %H:%M:%S"),
+                entry.end.unwrap().format("%Y-%m-%d %H:%M:%S")
+            ),
+            Problem::MultipleRunning { sheet, entries } => format!(
+                "Sheet {} has {} running entries: {}",
+                sheet,
+                entries.len(),
+                entries
+                    .iter()
+                    .map(|e| e.id.unwrap().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Problem::EmptyCurrentSheet { sheet, .. } => {
+                format!("The current sheet {} has no entries", sheet)
+            }
+        }
+    }
+
+    fn repair_description(&self) -> String {
+        match self {
+            Problem::BrokenTimestamp {
+                repaired: Some(dt), ..
+            } => format!("rewriting it as {}", dt.format("%Y-%m-%d %H:%M:%S")),
+            Problem::BrokenTimestamp { repaired: None, .. } => {
+                "moving the entry to the trash".to_string()
+            }
+            Problem::EndBeforeStart(_) => "swapping the start and the end".to_string(),
+            Problem::MultipleRunning { .. } => {
+                "stopping each entry when the following one started".to_string()
+            }
+            Problem::EmptyCurrentSheet { replacement, .. } => {
+                format!("switching to sheet {}", replacement)
+            }
+        }
+    }
+
+    fn repair(&self, state: &mut State) -> Result<()> {
+        match self {
+            Problem::BrokenTimestamp {
+                id,
+                column,
+                repaired,
+                ..
+            } => match repaired {
+                Some(dt) => update_entry_timestamp(id, column, *dt, &state.database)?,
+                None => remove_entry_by_id(id, &state.database)?,
+            },
+            Problem::EndBeforeStart(entry) => {
+                let mut entry = entry.clone();
+                let end = entry.end.unwrap();
+
+                entry.end = Some(entry.start);
+                entry.start = end;
+
+                update_entry(&entry, &state.database)?;
+            }
+            Problem::MultipleRunning { entries, .. } => {
+                // The entries are sorted, and the last one keeps running
+                for pair in entries.windows(2) {
+                    let mut entry = pair[0].clone();
+                    entry.stop(pair[1].start);
+
+                    update_entry(&entry, &state.database)?;
+                }
+            }
+            Problem::EmptyCurrentSheet { replacement, .. } => {
+                state.change_sheet(replacement)?;
+            }
+        };
+
+        Ok(())
+    }
+}
+
+struct Doctor<'a> {
+    fix: bool,
+    config: &'a Config,
+    found: usize,
+    repaired: usize,
+    has_snapshot: bool,
+}
+
+impl Doctor<'_> {
+    fn handle(&mut self, problems: Vec<Problem>, state: &mut State) -> Result<usize> {
+        let mut unrepaired = 0;
+
+        for problem in problems {
+            self.found += 1;
+
+            println!(
+                "{} {}",
+                style_string("Problem:", Styles::Error),
+                problem.describe()
+            );
+
+            let repair = problem.repair_description();
+
+            let confirmed = self.fix
+                || confirm_action(&format!("Do you want to repair it by {}? (y/N)", repair));
+
+            if !confirmed {
+                unrepaired += 1;
+                continue;
+            }
+
+            // Keep a copy of the data before the first change
+            if !self.has_snapshot {
+                let snapshot = take_snapshot("before-doctor", self.config, state)?;
+                println!(
+                    "{} {}",
+                    style_string("Saved a snapshot of the database to:", Styles::Message),
+                    snapshot.display()
+                );
+                self.has_snapshot = true;
+            }
+
+            problem.repair(state)?;
+            self.repaired += 1;
+
+            println!(
+                "{} {}",
+                style_string("Repaired by", Styles::Message),
+                repair
+            );
+        }
+
+        Ok(unrepaired)
+    }
+}
+
+pub fn run_doctor(fix: &bool, config: &Config, state: &mut State) -> Result<()> {
+    let mut doctor = Doctor {
+        fix: *fix,
+        config,
+        found: 0,
+        repaired: 0,
+        has_snapshot: false,
+    };
+
+    // Corruption can't be repaired here, the only way out is a backup
+    let integrity = integrity_check(&state.database)?;
+    if integrity != ["ok"] {
+        doctor.found += 1;
+
+        println!(
+            "{} The database file is corrupted:",
+            style_string("Problem:", Styles::Error)
+        );
+        for message in &integrity {
+            println!("  {}", message);
+        }
+        println!(
+            "{}",
+            style_string(
+                "Restore a backup with `t restore`. The snapshots are listed by `t backup list`.",
+                Styles::Message
+            )
+        );
+    }
+
+    let problems = find_broken_timestamps(state)?;
+    let unrepaired = doctor.handle(problems, state)?;
+
+    // The other checks need every entry to be readable
+    if unrepaired > 0 {
+        println!(
+            "{}",
+            style_string(
+                "The remaining checks need the broken timestamps to be repaired first.",
+                Styles::Message
+            )
+        );
+    } else {
+        let problems = find_entry_problems(state)?;
+        doctor.handle(problems, state)?;
+    }
+
+    // Some migrations can't be applied on broken data, so they are retried
+    if doctor.repaired > 0 {
+        create_tables(&state.database)?;
+    }
+
+    match doctor.found {
+        0 => println!("{}", style_string("No problems found.", Styles::Message)),
+        found => println!(
+            "{} {} / {}",
+            style_string("Problems repaired:", Styles::Message),
+            doctor.repaired,
+            found
+        ),
+    };
+
+    Ok(())
+}
+
+fn find_broken_timestamps(state: &State) -> Result<Vec<Problem>> {
+    let mut problems = Vec::new();
+
+    for (id, start, end) in get_entry_timestamps(&state.database)? {
+        let columns = [("start", Some(start)), ("end", end)];
+
+        for (column, value) in columns {
+            let Some(value) = value else {
+                continue;
+            };
+
+            if str_to_datetime(&value).is_err() {
+                problems.push(Problem::BrokenTimestamp {
+                    id,
+                    column,
+                    repaired: repair_timestamp(&value),
+                    value,
+                });
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+fn find_entry_problems(state: &State) -> Result<Vec<Problem>> {
+    let mut problems = Vec::new();
+    let mut entries = get_entries_to_check(&state.database)?;
+    entries.sort_by_key(|e| e.start);
+
+    for entry in &entries {
+        if entry.end.is_some_and(|end| end < entry.start) {
+            problems.push(Problem::EndBeforeStart(entry.clone()));
+        }
+    }
+
+    let mut running: HashMap<&str, Vec<Entry>> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.end.is_none()) {
+        running.entry(&entry.sheet).or_default().push(entry.clone());
+    }
+
+    let mut running: Vec<_> = running.into_iter().filter(|(_, e)| e.len() > 1).collect();
+    running.sort_by_key(|(sheet, _)| sheet.to_string());
+
+    for (sheet, entries) in running {
+        problems.push(Problem::MultipleRunning {
+            sheet: sheet.to_string(),
+            entries,
+        });
+    }
+
+    // A current sheet without entries is only a problem if other sheets have some,
+    // since new sheets are empty until the first check in.
+    let sheets = get_sheets_to_check(&state.database)?;
+
+    if !sheets.is_empty() && !sheets.contains(&state.current_sheet) {
+        let replacement = match sheets.contains(&state.last_sheet) {
+            true => state.last_sheet.clone(),
+            false => sheets[0].clone(),
+        };
+
+        problems.push(Problem::EmptyCurrentSheet {
+            sheet: state.current_sheet.clone(),
+            replacement,
+        });
+    }
+
+    Ok(problems)
+}
+
+fn repair_timestamp(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value.trim()) {
+        return Some(dt.with_timezone(&Local));
+    }
+
+    LENIENT_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+        .map(|dt| Local.from_utc_datetime(&dt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_repair_timestamp() {
+        let expected = Utc.with_ymd_and_hms(2024, 3, 1, 10, 30, 0).unwrap();
+
+        assert_eq!(repair_timestamp("2024-03-01T10:30:00Z").unwrap(), expected);
+        assert_eq!(repair_timestamp("2024-03-01T10:30:00").unwrap(), expected);
+        assert_eq!(repair_timestamp("2024-03-01 10:30").unwrap(), expected);
+
+        assert!(repair_timestamp("yesterday").is_none());
+        assert!(repair_timestamp("").is_none());
+    }
+}
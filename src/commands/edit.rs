@@ -2,7 +2,9 @@ use anyhow::Result;
 use langtime::parse;
 
 use crate::commands::display::{print_all_tasks_readable, ReadableOptions};
+use crate::commands::sheet::is_read_only;
 use crate::database::{get_entry_by_id, running_entry, update_entry};
+use crate::hooks::{Hook, PendingHook};
 use crate::style::{style_string, Styles};
 use crate::State;
 
@@ -12,9 +14,10 @@ pub fn edit_task(
     end: &Option<String>,
     move_to: &Option<String>,
     notes: &Option<String>,
+    force: &bool,
     state: &mut State,
 ) -> Result<()> {
-    let running_entry = running_entry(&state.database, &state.current_sheet)?;
+    let running_entry = running_entry(&state.database, state.active_sheet())?;
 
     let entry = if let Some(id) = id {
         get_entry_by_id(id, &state.database)?
@@ -33,6 +36,17 @@ pub fn edit_task(
     // This will be fine because of the preceding if statement
     let mut entry = entry.unwrap_or_else(|| running_entry.unwrap());
 
+    // Both the sheet the task is in and the one it is moved to must be writable
+    if is_read_only(&entry.sheet, force, state)? {
+        return Ok(());
+    }
+
+    if let Some(move_to) = move_to {
+        if is_read_only(move_to, force, state)? {
+            return Ok(());
+        }
+    }
+
     if let Some(start) = start {
         entry.start = parse(start)?;
     }
@@ -58,7 +72,13 @@ pub fn edit_task(
     options.show_headings = true;
     options.show_ids = true;
 
-    print_all_tasks_readable("", &vec![entry], &options);
+    print_all_tasks_readable("", &vec![entry.clone()], &options);
+
+    state.pending_hooks.push(PendingHook {
+        hook: Hook::PostEdit,
+        sheet: entry.sheet.clone(),
+        entry: Some(entry),
+    });
 
     Ok(())
 }
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local};
+use std::io::IsTerminal;
+
+use crate::commands::sheet::is_read_only;
+use crate::config::Config;
+use crate::database::{create_entry, get_all_entries};
+use crate::style::{style_string, Styles};
+use crate::utils::{ask, format_duration, local_datetime, parse_duration};
+use crate::Entry;
+use crate::State;
+
+pub fn fill_gaps(
+    day: Option<DateTime<Local>>,
+    min: &str,
+    config: &Config,
+    state: &State,
+) -> Result<()> {
+    let min = parse_duration(min)?;
+    let day = day.unwrap_or(Local::now()).date_naive();
+
+    if !config.working_days.contains(&day.weekday()) {
+        println!(
+            "{} {}",
+            style_string("Not a working day:", Styles::Message),
+            day.format("%a %b %d, %Y")
+        );
+        return Ok(());
+    }
+
+    // Only the working hours are checked, and only up to now
+    let (work_start, work_end) = config.working_hours;
+    let from = local_datetime(day.and_time(work_start));
+    let to = local_datetime(day.and_time(work_end)).min(Local::now());
+
+    // The gaps are the times without any task, in any sheet
+    let intervals: Vec<_> = get_all_entries(&state.database)?
+        .iter()
+        .map(|e| (e.start, e.end.unwrap_or(Local::now())))
+        .collect();
+
+    let gaps = find_gaps(&intervals, from, to, min);
+
+    if gaps.is_empty() {
+        println!("{}", style_string("There are no gaps.", Styles::Message));
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        style_string("Untracked time on", Styles::Title),
+        day.format("%a %b %d, %Y")
+    );
+
+    for (start, end) in &gaps {
+        println!(
+            "  {} - {}  {}",
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            format_duration(&(*end - *start))
+        );
+    }
+
+    // The gaps can only be filled when there is someone to answer
+    if !std::io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    let mut filled = 0;
+
+    for (start, end) in gaps {
+        println!();
+        println!(
+            "{} {} - {}",
+            style_string("Gap:", Styles::Title),
+            start.format("%H:%M"),
+            end.format("%H:%M")
+        );
+
+        let sheet = ask("Sheet to assign it to (leave empty to skip):");
+
+        if sheet.is_empty() || is_read_only(&sheet, &false, state)? {
+            continue;
+        }
+
+        let note = ask("Task description:");
+
+        let mut entry = Entry::start(&note, &sheet, start);
+        entry.stop(end);
+        create_entry(&entry, &state.database)?;
+
+        filled += 1;
+    }
+
+    println!(
+        "{} {}",
+        style_string("Gaps filled:", Styles::Message),
+        filled
+    );
+
+    Ok(())
+}
+
+// The intervals between from and to that are not covered by any of the given
+// ones, and that are at least min long
+fn find_gaps(
+    intervals: &[(DateTime<Local>, DateTime<Local>)],
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    min: Duration,
+) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    let mut intervals = intervals.to_vec();
+    intervals.sort();
+
+    let mut gaps = Vec::new();
+    let mut covered_until = from;
+
+    for (start, end) in intervals {
+        if start >= to {
+            break;
+        }
+
+        if start > covered_until {
+            gaps.push((covered_until, start));
+        }
+
+        covered_until = covered_until.max(end);
+    }
+
+    if covered_until < to {
+        gaps.push((covered_until, to));
+    }
+
+    gaps.retain(|(start, end)| *end - *start >= min);
+
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_find_gaps() {
+        let at = |h, m| Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+
+        let intervals = vec![
+            (at(8, 0), at(9, 30)),
+            (at(10, 0), at(11, 0)),
+            (at(10, 30), at(12, 0)),
+            (at(12, 5), at(13, 0)),
+        ];
+
+        let gaps = find_gaps(&intervals, at(9, 0), at(18, 0), Duration::minutes(10));
+        assert_eq!(gaps, vec![(at(9, 30), at(10, 0)), (at(13, 0), at(18, 0))]);
+
+        let gaps = find_gaps(&[], at(9, 0), at(18, 0), Duration::minutes(10));
+        assert_eq!(gaps, vec![(at(9, 0), at(18, 0))]);
+    }
+}
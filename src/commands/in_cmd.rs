@@ -1,52 +1,36 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
 
-use crate::database::running_entry;
-use crate::database::write_entry;
+use crate::commands::timer::{print_refusal, start_entry};
+use crate::config::Config;
 use crate::style::style_string;
 use crate::style::Styles;
-use crate::Entry;
 use crate::State;
 
-pub fn start_task(task: &str, at: Option<DateTime<Local>>, switch: &bool, state: &State) -> Result<()> {
+// The arguments mirror the options of `t in`
+#[allow(clippy::too_many_arguments)]
+pub fn start_task(
+    task: &str,
+    at: Option<DateTime<Local>>,
+    switch: &bool,
+    sheet: Option<&String>,
+    tags: &[String],
+    force: &bool,
+    config: &Config,
+    state: &mut State,
+) -> Result<()> {
     let start = at.unwrap_or(Local::now());
+    let sheet = sheet.cloned().unwrap_or(state.active_sheet().to_string());
 
-    let cur_task = running_entry(&state.database, &state.current_sheet)?;
-
-    let mut has_stopped_task = false;
-
-    // Check if task is already active and eventually stop it
-    if let Some(mut cur_task) = cur_task {
-        // If you shouldn't switch, notify the user and quit
-        if !switch {
-            println!(
-                "{} {}",
-                style_string("Already checked into sheet:", Styles::Message),
-                cur_task.sheet
-            );
-            return Ok(());
-        }
-
-        // If you can't switch, notify the user
-        if cur_task.start > start {
-            println!(
-                "{}",
-                style_string("Cannot switch to the new task, because it would stop the previous task before it was started.", Styles::Message)
-            );
-            return Ok(())
-        }
-
-        // Stop the previous task
-        cur_task.stop(start);
-        write_entry(&cur_task, &state.database)?;
-        has_stopped_task = true;
-    }
-
-    let entry = Entry::start(task, &state.current_sheet, start);
-
-    write_entry(&entry, &state.database)?;
-
-    let message = match has_stopped_task {
+    let output = state.hook_output;
+    let res = start_entry(task, &sheet, start, *switch, tags, *force, config, output, state);
+
+    let changes = match res {
+        Ok(changes) => changes,
+        Err(e) => return print_refusal(e),
+    };
+
+    let message = match changes.stopped.is_some() {
         true => "Previous task stopped and checked into sheet:",
         false => "Checked into sheet:"
     };
@@ -54,8 +38,10 @@ pub fn start_task(task: &str, at: Option<DateTime<Local>>, switch: &bool, state:
     println!(
         "{} {}",
         style_string(message, Styles::Message),
-        entry.sheet
+        changes.entry.sheet
     );
 
+    state.pending_hooks.extend(changes.hooks);
+
     Ok(())
 }
@@ -1,5 +1,6 @@
 use anyhow::Result;
 
+use crate::commands::sheet::is_read_only;
 use crate::database::{
     get_all_sheets, get_entry_by_id, remove_entries_by_sheet, remove_entry_by_id,
 };
@@ -7,7 +8,7 @@ use crate::style::{style_string, Styles};
 use crate::utils::confirm_action;
 use crate::State;
 
-pub fn kill_task(id: &usize, state: &mut State) -> Result<()> {
+pub fn kill_task(id: &usize, yes: &bool, force: &bool, state: &mut State) -> Result<()> {
     let entry = get_entry_by_id(id, &state.database)?;
 
     // Guard for non-existent entries
@@ -22,21 +23,37 @@ pub fn kill_task(id: &usize, state: &mut State) -> Result<()> {
 
     let entry = entry.unwrap();
 
-    if !confirm_action(&format!(
-        "Are you sure you want to remove entry {} (y/N)?",
-        entry.name
-    )) {
+    if is_read_only(&entry.sheet, force, state)? {
+        return Ok(());
+    }
+
+    // The entry can be restored from the trash, so the confirmation can be skipped
+    if !yes
+        && !confirm_action(&format!(
+            "Are you sure you want to remove entry {} (y/N)?",
+            entry.name
+        ))
+    {
         return Ok(());
     }
 
     remove_entry_by_id(id, &state.database)?;
 
-    println!("{} {}", style_string("Removed entry:", Styles::Message), id);
+    println!(
+        "{} {}",
+        style_string("Moved entry to the trash:", Styles::Message),
+        id
+    );
+    println!(
+        "{} t trash restore {}",
+        style_string("To bring it back, run:", Styles::Message),
+        id
+    );
 
     Ok(())
 }
 
-pub fn kill_sheet(sheet: &str, state: &mut State) -> Result<()> {
+pub fn kill_sheet(sheet: &str, yes: &bool, force: &bool, state: &mut State) -> Result<()> {
     let sheets = get_all_sheets(&state.database)?;
 
     // Guard for non-existent sheets
@@ -49,7 +66,11 @@ pub fn kill_sheet(sheet: &str, state: &mut State) -> Result<()> {
         return Ok(());
     }
 
-    if !confirm_action(&format!("Are you sure you want to remove sheet {}?", sheet)) {
+    if is_read_only(sheet, force, state)? {
+        return Ok(());
+    }
+
+    if !yes && !confirm_action(&format!("Are you sure you want to remove sheet {}?", sheet)) {
         return Ok(());
     }
 
@@ -59,7 +80,7 @@ pub fn kill_sheet(sheet: &str, state: &mut State) -> Result<()> {
     if state.current_sheet == sheet {
         move_to_last_sheet(state)?;
     } else if state.last_sheet == sheet {
-        state.last_sheet = "default".to_string()
+        state.update_last_sheet("default")?;
     }
 
     println!(
@@ -67,6 +88,10 @@ pub fn kill_sheet(sheet: &str, state: &mut State) -> Result<()> {
         style_string("Removed sheet:", Styles::Message),
         sheet
     );
+    println!(
+        "{}",
+        style_string("Its entries were moved to the trash, see `t trash list`.", Styles::Message)
+    );
 
     Ok(())
 }
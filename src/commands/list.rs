@@ -5,23 +5,30 @@ use tabled::settings::object::Rows;
 use tabled::settings::themes::Colorization;
 use tabled::settings::{Color, Style};
 
-use crate::database::{get_all_sheets, get_sheet_entries};
+use crate::chart::{last_days, sparkline, use_unicode};
+use crate::database::{get_all_sheets, get_archived_sheets, get_sheet_entries};
 use crate::style::{style_string, Styles};
 use crate::utils::{format_duration, time_from_now};
 use crate::State;
 
-pub fn list_sheets(state: &State) -> Result<()> {
+pub fn list_sheets(show_archived: &bool, state: &State) -> Result<()> {
     let mut sheets = get_all_sheets(&state.database)?;
+    let archived = get_archived_sheets(&state.database)?;
+
+    if !show_archived {
+        sheets.retain(|s| !archived.contains(s));
+    }
 
     if sheets.is_empty() {
         sheets.push(state.current_sheet.to_string());
     }
 
     let mut builder = Builder::new();
+    let unicode = use_unicode();
 
     println!("{}", style_string("Timesheets:", Styles::Title));
 
-    builder.push_record(vec!["Name", "Running", "Today", "Total time"]);
+    builder.push_record(vec!["Name", "Running", "Today", "Total time", "Last 14 days"]);
 
     for sheet in sheets {
         let entries = get_sheet_entries(&sheet, &state.database)?;
@@ -48,7 +55,12 @@ pub fn list_sheets(state: &State) -> Result<()> {
         } else if sheet == state.last_sheet {
             format!("{}{}", "-", sheet)
         } else {
-            sheet
+            sheet.clone()
+        };
+
+        let s = match archived.contains(&sheet) {
+            true => format!("{} (archived)", s),
+            false => s,
         };
 
         builder.push_record(vec![
@@ -56,6 +68,7 @@ pub fn list_sheets(state: &State) -> Result<()> {
             format_duration(&running_time),
             format_duration(&today_total),
             format_duration(&total),
+            sparkline(&last_days(&entries, 14), unicode),
         ]);
     }
 
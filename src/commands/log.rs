@@ -0,0 +1,68 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+
+use crate::commands::display::{print_all_tasks_readable, ReadableOptions};
+use crate::commands::sheet::is_read_only;
+use crate::commands::template::TaskArgs;
+use crate::database::write_entry;
+use crate::style::{style_string, Styles};
+use crate::utils::parse_duration;
+use crate::Entry;
+use crate::State;
+
+// Adds a task that is already over, starting at the given time or ending now
+pub fn log_task(
+    args: TaskArgs,
+    at: Option<DateTime<Local>>,
+    duration: Option<&String>,
+    force: &bool,
+    state: &State,
+) -> Result<()> {
+    // The given duration takes precedence over the template's one
+    let duration = match duration {
+        Some(d) => Some(parse_duration(d)?),
+        None => args.duration,
+    };
+
+    let Some(duration) = duration.filter(|d| *d > Duration::zero()) else {
+        println!(
+            "{}",
+            style_string(
+                "A duration is needed, either from a template or with --duration.",
+                Styles::Message
+            )
+        );
+        return Ok(());
+    };
+
+    let sheet = args.sheet.unwrap_or(state.active_sheet().to_string());
+
+    if is_read_only(&sheet, force, state)? {
+        return Ok(());
+    }
+
+    let start = at.unwrap_or(Local::now() - duration);
+    let mut entry = Entry::start(&args.note, &sheet, start);
+    entry.stop(start + duration);
+
+    // The default tags of a bound sheet are added to the given ones
+    if let Some(binding) = state.binding.as_ref().filter(|b| b.sheet == sheet) {
+        entry.tags.extend(binding.tags.iter().cloned());
+    }
+    entry.tags.extend(args.tags);
+    entry.tags.sort();
+    entry.tags.dedup();
+
+    write_entry(&entry, &state.database)?;
+    entry.id = Some(state.database.last_insert_rowid() as usize);
+
+    println!("{} {}", style_string("Logged task in sheet:", Styles::Message), sheet);
+
+    let mut options = ReadableOptions::new();
+    options.show_headings = true;
+    options.show_ids = true;
+
+    print_all_tasks_readable("", &vec![entry], &options);
+
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+
+use crate::commands::display::{print_all_tasks_readable, ReadableOptions};
+use crate::commands::sheet::is_read_only;
+use crate::database::{get_entry_by_id, get_sheet_entries, remove_entry_by_id, update_entry};
+use crate::style::{style_string, Styles};
+use crate::utils::{confirm_action, day_begin, day_end, parse_duration};
+use crate::Entry;
+use crate::State;
+
+pub fn merge_tasks(ids: &[usize], force: &bool, state: &State) -> Result<()> {
+    let mut entries = Vec::new();
+
+    for id in ids {
+        match get_entry_by_id(id, &state.database)? {
+            Some(entry) if !entries.contains(&entry) => entries.push(entry),
+            Some(_) => {}
+            None => {
+                println!(
+                    "{} {}",
+                    style_string("Entry not found. Id:", Styles::Message),
+                    id
+                );
+                return Ok(());
+            }
+        };
+    }
+
+    if let Some(problem) = merge_problem(&entries) {
+        println!("{}", style_string(problem, Styles::Message));
+        return Ok(());
+    }
+
+    if is_read_only(&entries[0].sheet, force, state)? {
+        return Ok(());
+    }
+
+    let merged = merge_entries(&entries, state)?;
+
+    println!("{}", style_string("Tasks merged in:", Styles::Message));
+
+    let mut options = ReadableOptions::new();
+    options.show_headings = true;
+    options.show_ids = true;
+
+    print_all_tasks_readable("", &vec![merged], &options);
+
+    Ok(())
+}
+
+pub fn auto_merge_tasks(
+    gap: &str,
+    sheet: Option<&String>,
+    day: Option<DateTime<Local>>,
+    force: &bool,
+    state: &State,
+) -> Result<()> {
+    let gap = parse_duration(gap)?;
+    let sheet = sheet.map(|s| s.as_str()).unwrap_or(state.active_sheet());
+    let day = day.unwrap_or(Local::now());
+
+    if is_read_only(sheet, force, state)? {
+        return Ok(());
+    }
+
+    let mut entries = get_sheet_entries(sheet, &state.database)?;
+    entries.retain(|e| e.start >= day_begin(day) && e.start <= day_end(day));
+    entries.sort_by_key(|e| e.start);
+
+    let groups = find_fragments(&entries, gap);
+
+    if groups.is_empty() {
+        println!(
+            "{}",
+            style_string("There are no fragments to merge.", Styles::Message)
+        );
+        return Ok(());
+    }
+
+    // Preview
+    let mut options = ReadableOptions::new();
+    options.show_headings = true;
+    options.show_ids = true;
+
+    for group in &groups {
+        println!(
+            "{} {}",
+            style_string("These tasks will be merged:", Styles::Message),
+            group[0].description()
+        );
+        print_all_tasks_readable(sheet, group, &options);
+    }
+
+    if !confirm_action(&format!("Do you want to merge {} groups of tasks? (y/N)", groups.len())) {
+        return Ok(());
+    }
+
+    for group in &groups {
+        merge_entries(group, state)?;
+    }
+
+    println!(
+        "{} {}",
+        style_string("Groups of tasks merged:", Styles::Message),
+        groups.len()
+    );
+
+    Ok(())
+}
+
+// Why the entries can't be merged, if they can't
+pub fn merge_problem(entries: &[Entry]) -> Option<&'static str> {
+    if entries.len() < 2 {
+        return Some("At least two different tasks are needed to merge them.");
+    }
+
+    if entries.iter().any(|e| e.sheet != entries[0].sheet) {
+        return Some("Only tasks in the same sheet can be merged.");
+    }
+
+    None
+}
+
+// Replaces the entries with a single one, kept in the earliest of them. The
+// others are moved to the trash.
+pub fn merge_entries(entries: &[Entry], state: &State) -> Result<Entry> {
+    let merged = merged_entry(entries);
+
+    // The others go first, since a sheet can only have one running task
+    for entry in entries {
+        if entry.id != merged.id {
+            remove_entry_by_id(&entry.id.unwrap(), &state.database)?;
+        }
+    }
+
+    update_entry(&merged, &state.database)?;
+
+    Ok(merged)
+}
+
+// The entry spanning all the given ones, with their notes and tags combined
+fn merged_entry(entries: &[Entry]) -> Entry {
+    let mut merged = entries.iter().min_by_key(|e| e.start).unwrap().clone();
+
+    // A running task keeps the merged one running
+    merged.end = match entries.iter().any(|e| e.end.is_none()) {
+        true => None,
+        false => entries.iter().filter_map(|e| e.end).max(),
+    };
+
+    let mut notes: Vec<&str> = Vec::new();
+    let mut sorted: Vec<&Entry> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.start);
+
+    for entry in sorted {
+        if !entry.name.is_empty() && !notes.contains(&entry.name.as_str()) {
+            notes.push(&entry.name);
+        }
+
+        merged.tags.extend(entry.tags.iter().cloned());
+    }
+
+    merged.name = notes.join("; ");
+    merged.tags.sort();
+    merged.tags.dedup();
+
+    merged
+}
+
+// Groups the consecutive entries with the same note, separated by at most the
+// given gap. The entries must be sorted by their start.
+fn find_fragments(entries: &[Entry], gap: Duration) -> Vec<Vec<Entry>> {
+    let mut groups: Vec<Vec<Entry>> = Vec::new();
+    let mut current: Vec<Entry> = Vec::new();
+
+    for entry in entries {
+        let follows = current.last().is_some_and(|prev| {
+            prev.name == entry.name && prev.end.is_some_and(|end| entry.start - end <= gap)
+        });
+
+        if !follows {
+            if current.len() > 1 {
+                groups.push(current);
+            }
+
+            current = Vec::new();
+        }
+
+        current.push(entry.clone());
+    }
+
+    if current.len() > 1 {
+        groups.push(current);
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(id: usize, name: &str, start: (u32, u32), end: Option<(u32, u32)>) -> Entry {
+        let at = |(h, m)| Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+
+        let mut entry = Entry::start(name, "default", at(start));
+        entry.id = Some(id);
+        entry.end = end.map(at);
+        entry
+    }
+
+    #[test]
+    fn test_find_fragments() {
+        let entries = vec![
+            entry(1, "coding", (9, 0), Some((9, 30))),
+            entry(2, "coding", (9, 31), Some((10, 0))),
+            entry(3, "coding", (10, 1), Some((10, 30))),
+            entry(4, "coding", (11, 0), Some((11, 30))),
+            entry(5, "review", (11, 30), Some((12, 0))),
+            entry(6, "review", (12, 0), None),
+        ];
+
+        let groups = find_fragments(&entries, Duration::minutes(2));
+        let ids: Vec<Vec<usize>> = groups
+            .iter()
+            .map(|g| g.iter().map(|e| e.id.unwrap()).collect())
+            .collect();
+
+        assert_eq!(ids, vec![vec![1, 2, 3], vec![5, 6]]);
+    }
+
+    #[test]
+    fn test_merged_entry() {
+        let mut second = entry(2, "review", (10, 0), Some((11, 0)));
+        second.tags = vec!["acme".to_string()];
+
+        let entries = vec![
+            second,
+            entry(1, "coding", (9, 0), Some((9, 30))),
+            entry(3, "coding", (9, 45), Some((10, 15))),
+        ];
+
+        let merged = merged_entry(&entries);
+        assert_eq!(merged.id, Some(1));
+        assert_eq!(merged.start, entries[1].start);
+        assert_eq!(merged.end, entries[0].end);
+        assert_eq!(merged.name, "coding; review");
+        assert_eq!(merged.tags, vec!["acme".to_string()]);
+
+        let running = vec![
+            entry(1, "coding", (9, 0), Some((9, 30))),
+            entry(2, "coding", (9, 30), None),
+        ];
+        assert!(merged_entry(&running).end.is_none());
+    }
+}
@@ -1,19 +1,59 @@
+mod api;
+mod backup;
+mod completions;
 mod current;
 mod display;
+mod doctor;
 mod edit;
+mod gaps;
 mod in_cmd;
 mod kill;
 mod list;
+mod log;
+mod merge;
 mod month;
 mod out;
+mod prompt;
+mod rpc;
+mod search;
+mod serve;
 mod sheet;
+mod split;
+mod status;
+mod template;
+mod timeline;
+mod timer;
+mod trash;
+mod tui;
+mod week;
+mod year;
 
+pub use backup::{auto_snapshot, backup_database, list_snapshots, restore_database};
+pub use completions::{
+    complete_ids, complete_notes, complete_sheets, print_completions, COMPLETE_VAR,
+};
 pub use current::current_task;
-pub use display::display_tasks;
+pub use display::{display_tasks, ReadableOptions, SheetFilter};
+pub use doctor::run_doctor;
 pub use edit::edit_task;
+pub use gaps::fill_gaps;
 pub use in_cmd::start_task;
 pub use kill::{kill_sheet, kill_task};
 pub use list::list_sheets;
+pub use log::log_task;
+pub use merge::{auto_merge_tasks, merge_tasks};
 pub use month::display_month;
 pub use out::stop_task;
-pub use sheet::{checkout_sheet, rename_sheet};
+pub use prompt::{print_cached_prompt, print_prompt};
+pub use rpc::run_rpc;
+pub use search::search_tasks;
+pub use serve::serve;
+pub use sheet::{archive_sheet, bind_sheet, checkout_sheet, rename_sheet};
+pub use split::split_task;
+pub use status::{print_status, StatusFormat};
+pub use template::{add_template, expand_task, list_templates, remove_template};
+pub use timeline::display_timeline;
+pub use trash::{list_trash, purge_trash, restore_from_trash};
+pub use tui::run_tui;
+pub use week::display_week;
+pub use year::display_year;
@@ -1,20 +1,29 @@
 use anyhow::Result;
-use chrono::Local;
 
-use crate::commands::display_tasks;
-use crate::utils::get_month_boundaries;
+use crate::commands::{display_tasks, ReadableOptions, SheetFilter};
+use crate::config::Config;
+use crate::range::DateRange;
 use crate::State;
 
 pub fn display_month(
     json: &bool,
-    ids: &bool,
+    options: &ReadableOptions,
     month: Option<&String>,
     sheet: Option<&String>,
+    config: &Config,
     state: &mut State,
 ) -> Result<()> {
-    let now = Local::now().format("%Y-%m").to_string();
-    let month = month.unwrap_or(&now);
-    let (start, end) = get_month_boundaries(month)?;
+    // Any period is accepted, but it defaults to the current month
+    let month = month.map(|m| m.as_str()).unwrap_or("this month");
+    let range = DateRange::parse(month, config.week_start)?;
 
-    display_tasks(json, sheet, Some(start), Some(end), &true, ids, state)
+    display_tasks(
+        json,
+        SheetFilter::Sheet(sheet),
+        Some(range.start),
+        Some(range.end),
+        &true,
+        options,
+        state,
+    )
 }
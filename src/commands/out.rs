@@ -1,40 +1,33 @@
 use anyhow::Result;
 use chrono::{DateTime, Local};
 
-use crate::database::{running_entry, write_entry};
+use crate::commands::timer::{print_refusal, stop_entry};
+use crate::config::Config;
 use crate::style::{style_string, Styles};
 use crate::State;
 
-pub fn stop_task(at: Option<DateTime<Local>>, state: &mut State) -> Result<()> {
+pub fn stop_task(
+    at: Option<DateTime<Local>>,
+    sheet: Option<&String>,
+    force: &bool,
+    config: &Config,
+    state: &mut State,
+) -> Result<()> {
     let end = at.unwrap_or(Local::now());
+    let sheet = sheet.map(|s| s.as_str()).unwrap_or(state.active_sheet());
 
-    let cur = running_entry(&state.database, &state.current_sheet)?;
-
-    match cur {
-        None => println!(
-            "{}",
-            style_string("There is no active task.", Styles::Message)
-        ),
-        Some(mut e) => {
-            // Check if the end is before the start
-            if e.start > end {
-                println!(
-                    "{}",
-                    style_string("Cannot stop a task before it started.", Styles::Message)
-                );
-                return Ok(());
-            }
+    let changes = match stop_entry(sheet, end, *force, config, state.hook_output, state) {
+        Ok(changes) => changes,
+        Err(e) => return print_refusal(e),
+    };
 
-            e.stop(end);
-            write_entry(&e, &state.database)?;
+    println!(
+        "{} {}",
+        style_string("Checked out of sheet:", Styles::Message),
+        changes.entry.sheet
+    );
 
-            println!(
-                "{} {}",
-                style_string("Checked out of sheet:", Styles::Message),
-                e.sheet
-            );
-        }
-    };
+    state.pending_hooks.extend(changes.hooks);
 
     Ok(())
 }
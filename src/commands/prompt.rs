@@ -0,0 +1,43 @@
+use anyhow::Result;
+
+use crate::binding::Binding;
+use crate::cache::PromptCache;
+use crate::config::Config;
+use crate::utils::time_from_now;
+use crate::State;
+
+// Fast path for the shell prompt: returns false if the cache can't be used
+pub fn print_cached_prompt(config: &Config) -> bool {
+    match PromptCache::load(config) {
+        Some(cache) => {
+            // A broken binding file is reported by the slow path
+            let binding = match Binding::find() {
+                Ok(binding) => binding,
+                Err(_) => return false,
+            };
+            let sheet = binding.map(|b| b.sheet).unwrap_or(cache.sheet.clone());
+
+            print_segment(&cache, &sheet);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn print_prompt(config: &Config, state: &State) -> Result<()> {
+    let cache = PromptCache::build(state)?;
+    cache.save(config)?;
+
+    print_segment(&cache, state.active_sheet());
+
+    Ok(())
+}
+
+fn print_segment(cache: &PromptCache, sheet: &str) {
+    // Nothing is printed when there is no running task, to keep the prompt clean
+    if let Some(start) = cache.running_start(sheet) {
+        let minutes = time_from_now(&start).num_minutes();
+
+        println!("⏱ {} {}:{:0>2}", sheet, minutes / 60, minutes % 60);
+    }
+}
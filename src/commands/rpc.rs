@@ -0,0 +1,251 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{stdin, stdout, BufRead, Write};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::commands::api::{
+    check_in, check_out, current, list_entries, list_sheets, patch_entry, ApiError, EntryPatch,
+    Reply,
+};
+use crate::config::Config;
+use crate::database::{
+    begin_transaction, commit_transaction, rollback_transaction, running_entries,
+};
+use crate::hooks::{post_hook_problems, HookOutput};
+use crate::Entry;
+use crate::State;
+
+// How often the database is checked for tasks started or stopped elsewhere
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// The standard JSON-RPC error codes, and ours for the missing entries and the conflicts
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const NOT_FOUND: i64 = -32001;
+const CONFLICT: i64 = -32002;
+
+// The methods, and whether they lock the database from the start.
+// Start and stop run the pre-hooks first, so they take the lock with their change.
+const METHODS: [(&str, bool); 6] = [
+    ("start", false),
+    ("stop", false),
+    ("current", false),
+    ("list", false),
+    ("entries.query", false),
+    ("entries.update", true),
+];
+
+#[derive(Deserialize)]
+struct UpdateParams {
+    id: usize,
+    #[serde(flatten)]
+    patch: EntryPatch,
+}
+
+pub fn run_rpc(config: &Config, state: &mut State) -> Result<()> {
+    // Stdout is only for the messages
+    state.hook_output = HookOutput::Stderr;
+
+    // The requests are read on their own thread, to watch the database in between.
+    // The lines are read as bytes, so that one that isn't UTF-8 can be answered.
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let mut input = stdin().lock();
+
+        loop {
+            let mut line = Vec::new();
+            match input.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            };
+        }
+    });
+
+    let mut running = running_entries(&state.database)?;
+
+    loop {
+        let message = receiver.recv_timeout(POLL_INTERVAL);
+
+        // Changes made by other processes since the last look
+        if let Some(now) = poll_running(state) {
+            if now != running {
+                let params = json!({ "running": now });
+                send(json!({ "jsonrpc": "2.0", "method": "running.changed", "params": params }))?;
+                running = now;
+            }
+        }
+
+        match message {
+            Ok(line) => {
+                let response = match String::from_utf8(line) {
+                    Ok(line) => handle_line(&line, config, state),
+                    Err(_) => Some(error(Value::Null, PARSE_ERROR, "Parse error: invalid UTF-8")),
+                };
+
+                if let Some(response) = response {
+                    send(response)?;
+                }
+
+                // The client already knows about its own changes
+                if let Some(now) = poll_running(state) {
+                    running = now;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+    }
+
+    Ok(())
+}
+
+// A busy database, e.g. during a `t in` from another terminal, only skips one look
+fn poll_running(state: &State) -> Option<Vec<Entry>> {
+    match running_entries(&state.database) {
+        Ok(running) => Some(running),
+        Err(e) => {
+            eprintln!("Warning: could not check the running tasks: {:#}", e);
+            None
+        }
+    }
+}
+
+fn send(message: Value) -> Result<()> {
+    let mut out = stdout().lock();
+    writeln!(out, "{}", message)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+// The response to a line, if it needs one: notifications are not answered
+fn handle_line(line: &str, config: &Config, state: &mut State) -> Option<Value> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e))),
+    };
+
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+
+    let (Some(method), Some("2.0")) = (method, request["jsonrpc"].as_str()) else {
+        return Some(error(id.unwrap_or(Value::Null), INVALID_REQUEST, "Invalid request."));
+    };
+
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = call(method, params, config, state);
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    })
+}
+
+fn call(
+    method: &str,
+    params: Value,
+    config: &Config,
+    state: &mut State,
+) -> std::result::Result<Value, (i64, String)> {
+    let Some((_, locks)) = METHODS.iter().find(|(name, _)| *name == method) else {
+        return Err((METHOD_NOT_FOUND, format!("Method not found: {}", method)));
+    };
+
+    dispatch(method, params, *locks, config, state).map_err(|e| (code(e.status), e.message))
+}
+
+// Each call runs in its own transaction, so a failed change leaves nothing behind
+fn dispatch(
+    method: &str,
+    params: Value,
+    locks: bool,
+    config: &Config,
+    state: &mut State,
+) -> Reply {
+    begin_transaction(&state.database, locks)?;
+
+    let reply = match method {
+        "start" => read_params(params).and_then(|params| check_in(params, config, state)),
+        "stop" => read_params(params).and_then(|params| check_out(params, config, state)),
+        "current" => current(state),
+        "list" => list_sheets(state),
+        "entries.query" => {
+            read_params(params).and_then(|query| list_entries(query, config, state))
+        }
+        "entries.update" => read_params(params)
+            .and_then(|UpdateParams { id, patch }| patch_entry(id, patch, state)),
+        _ => Err(ApiError::new(404, &format!("Method not found: {}", method))),
+    };
+
+    let hooks = std::mem::take(&mut state.pending_hooks);
+
+    match reply {
+        Ok(_) => commit_transaction(&state.database)?,
+        Err(_) => rollback_transaction(&state.database)?,
+    };
+
+    if reply.is_ok() {
+        for problem in post_hook_problems(&hooks, config, state.hook_output) {
+            eprintln!("Warning: {}", problem);
+        }
+    }
+
+    reply
+}
+
+// Missing params are the same as empty ones
+fn read_params<T: for<'a> Deserialize<'a>>(params: Value) -> std::result::Result<T, ApiError> {
+    let params = match params {
+        Value::Null => json!({}),
+        params => params,
+    };
+
+    serde_json::from_value(params)
+        .map_err(|e| ApiError::new(400, &format!("Invalid params: {}", e)))
+}
+
+fn code(status: u16) -> i64 {
+    match status {
+        400 => INVALID_PARAMS,
+        404 => NOT_FOUND,
+        409 => CONFLICT,
+        _ => INTERNAL_ERROR,
+    }
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_params() {
+        let params: UpdateParams =
+            read_params(json!({ "id": 3, "name": "review", "tags": ["a"] })).ok().unwrap();
+
+        assert_eq!(params.id, 3);
+        assert_eq!(params.patch.name.as_deref(), Some("review"));
+        assert_eq!(params.patch.tags, Some(vec!["a".to_string()]));
+        assert!(params.patch.start.is_none());
+
+        assert!(read_params::<UpdateParams>(Value::Null).is_err());
+    }
+}
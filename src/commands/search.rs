@@ -0,0 +1,67 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+
+use crate::commands::display::{print_all_tasks_readable, ReadableOptions};
+use crate::database::search_entries;
+use crate::style::{style_string, Styles};
+use crate::utils::format_duration;
+use crate::Entry;
+use crate::State;
+
+pub fn search_tasks(
+    query: &str,
+    sheet: Option<&String>,
+    start: Option<DateTime<Local>>,
+    end: Option<DateTime<Local>>,
+    ids: &bool,
+    state: &State,
+) -> Result<()> {
+    let mut entries = search_entries(query, sheet.map(|s| s.as_str()), &state.database)?;
+
+    entries.retain(|e| {
+        if start.is_some() && e.start < start.unwrap() {
+            return false;
+        }
+
+        if end.is_some() && e.start > end.unwrap() {
+            return false;
+        }
+
+        true
+    });
+
+    if entries.is_empty() {
+        println!("{}", style_string("No tasks found.", Styles::Message));
+        return Ok(());
+    }
+
+    // One table for each sheet, starting from the one with the best match
+    let mut sheets: Vec<(String, Vec<Entry>)> = Vec::new();
+
+    for entry in &entries {
+        match sheets.iter_mut().find(|(s, _)| *s == entry.sheet) {
+            Some((_, sheet_entries)) => sheet_entries.push(entry.clone()),
+            None => sheets.push((entry.sheet.clone(), vec![entry.clone()])),
+        };
+    }
+
+    let mut options = ReadableOptions::complete();
+    options.show_ids = *ids;
+    options.show_partial_sum = false;
+
+    for (sheet, sheet_entries) in &sheets {
+        print_all_tasks_readable(sheet, sheet_entries, &options);
+    }
+
+    if sheets.len() > 1 {
+        let total: Duration = entries.iter().map(|e| e.get_duration()).sum();
+
+        println!(
+            "{} {}",
+            style_string("Total of all sheets:", Styles::Title),
+            format_duration(&total)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::env;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::commands::api::{
+    check_in, check_out, current, delete_entry, list_entries, list_sheets, patch_entry, ApiError,
+    EntryQuery,
+};
+use crate::config::Config;
+use crate::database::{begin_transaction, commit_transaction, rollback_transaction};
+use crate::hooks::run_post_hooks;
+use crate::style::{style_string, Styles};
+use crate::State;
+
+// The token can also be given through the environment, to keep it out of the process list
+const TOKEN_VARIABLE: &str = "TIMETRACK_TOKEN";
+
+type Reply = std::result::Result<(u16, Value), ApiError>;
+
+pub fn serve(
+    host: &str,
+    port: &u16,
+    token: Option<&String>,
+    config: &Config,
+    state: &mut State,
+) -> Result<()> {
+    let token = token.cloned().or(env::var(TOKEN_VARIABLE).ok());
+
+    let server = Server::http((host, *port))
+        .map_err(|e| anyhow!("Cannot listen on {}:{}: {}", host, port, e))?;
+
+    println!(
+        "{} http://{}:{}",
+        style_string("Listening on", Styles::Message),
+        host,
+        port
+    );
+
+    if token.is_none() && !is_local(host) {
+        println!(
+            "{}",
+            style_string(
+                "Warning: the API is reachable from other machines without a token.",
+                Styles::Error
+            )
+        );
+    }
+
+    for mut request in server.incoming_requests() {
+        let reply = check_request(&request, host, *port, token.as_deref())
+            .and_then(|_| handle(&mut request, config, state));
+
+        let (status, body) = match reply {
+            Ok(reply) => reply,
+            Err(e) => (e.status, json!({ "error": e.message })),
+        };
+
+        let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+
+        // A client that went away doesn't stop the server
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+// Web pages can send requests to a local server too, so the requests must show that
+// they come from a client that knows where the server is and what it speaks
+fn check_request(
+    request: &Request,
+    host: &str,
+    port: u16,
+    token: Option<&str>,
+) -> std::result::Result<(), ApiError> {
+    // A page on another site could reach the server through DNS rebinding, but its
+    // requests would still carry the name of that site
+    if is_local(host) && !header(request, "Host").is_some_and(|h| is_local_host_header(h, port)) {
+        return Err(ApiError::new(403, "The Host header must name this machine."));
+    }
+
+    if !authorized(request, token) {
+        return Err(ApiError::new(401, "Missing or wrong bearer token."));
+    }
+
+    // Browsers can't send JSON to another site without asking for permission first
+    let is_json = header(request, "Content-Type").is_some_and(|h| {
+        let media_type = h.split(';').next().unwrap_or("");
+        media_type.trim().eq_ignore_ascii_case("application/json")
+    });
+
+    if request.method() != &Method::Get && !is_json {
+        return Err(ApiError::new(415, "The Content-Type must be application/json."));
+    }
+
+    Ok(())
+}
+
+fn authorized(request: &Request, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+
+    header(request, "Authorization").is_some_and(|h| h == format!("Bearer {}", token))
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+// The Host header, e.g. "localhost:7777" or "[::1]", with or without the port
+fn is_local_host_header(value: &str, port: u16) -> bool {
+    let (name, header_port) = match value.rsplit_once(':') {
+        Some((name, header_port)) if !value.ends_with(']') => (name, Some(header_port)),
+        _ => (value, None),
+    };
+
+    let same_port = header_port.is_none_or(|p| p == port.to_string());
+
+    same_port && matches!(name, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+// Each request runs in its own transaction, so a failed change leaves nothing behind
+fn handle(request: &mut Request, config: &Config, state: &mut State) -> Reply {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|_| ApiError::new(400, "The request body is not valid UTF-8."))?;
+
+    // Checking in and out run the pre-hooks first, so they take the lock with their change
+    begin_transaction(&state.database, matches!(method, Method::Patch | Method::Delete))?;
+
+    let reply = route(&method, &segments, query, &body, config, state);
+    let hooks = std::mem::take(&mut state.pending_hooks);
+
+    match reply {
+        Ok(_) => commit_transaction(&state.database)?,
+        Err(_) => rollback_transaction(&state.database)?,
+    };
+
+    if reply.is_ok() {
+        run_post_hooks(&hooks, config);
+    }
+
+    reply
+}
+
+fn route(
+    method: &Method,
+    segments: &[&str],
+    query: &str,
+    body: &str,
+    config: &Config,
+    state: &mut State,
+) -> Reply {
+    match (method, segments) {
+        (Method::Get, ["entries"]) => Ok((200, list_entries(read_query(query)?, config, state)?)),
+        (Method::Patch, ["entries", id]) => {
+            Ok((200, patch_entry(parse_id(id)?, read_body(body)?, state)?))
+        }
+        (Method::Delete, ["entries", id]) => Ok((200, delete_entry(parse_id(id)?, state)?)),
+        (Method::Post, ["in"]) => Ok((201, check_in(read_body(body)?, config, state)?)),
+        (Method::Post, ["out"]) => Ok((200, check_out(read_body(body)?, config, state)?)),
+        (Method::Get, ["sheets"]) => Ok((200, list_sheets(state)?)),
+        (Method::Get, ["current"]) => Ok((200, current(state)?)),
+        _ => Err(ApiError::new(404, "Not found.")),
+    }
+}
+
+// Filters: sheet, start, end, range (e.g. "last week")
+fn read_query(query: &str) -> std::result::Result<EntryQuery, ApiError> {
+    let mut filters = EntryQuery::default();
+
+    for (key, value) in parse_query(query) {
+        match key.as_str() {
+            "sheet" => filters.sheet = Some(value),
+            "start" => filters.start = Some(value),
+            "end" => filters.end = Some(value),
+            "range" => filters.range = Some(value),
+            _ => return Err(ApiError::new(400, &format!("Unknown filter: {}", key))),
+        };
+    }
+
+    Ok(filters)
+}
+
+fn read_body<T: for<'a> Deserialize<'a> + Default>(body: &str) -> std::result::Result<T, ApiError> {
+    if body.trim().is_empty() {
+        return Ok(T::default());
+    }
+
+    serde_json::from_str(body)
+        .map_err(|e| ApiError::new(400, &format!("Invalid request body: {}", e)))
+}
+
+fn parse_id(id: &str) -> std::result::Result<usize, ApiError> {
+    id.parse()
+        .map_err(|_| ApiError::new(400, &format!("Invalid entry id: {}", id)))
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+// Percent-decoding, with "+" for spaces
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let byte = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        };
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn is_local(host: &str) -> bool {
+    matches!(host, "127.0.0.1" | "localhost" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse_query("sheet=client&range=last+week&start=2024-03-01%2010%3A00");
+
+        assert_eq!(
+            query,
+            vec![
+                ("sheet".to_string(), "client".to_string()),
+                ("range".to_string(), "last week".to_string()),
+                ("start".to_string(), "2024-03-01 10:00".to_string()),
+            ]
+        );
+
+        assert_eq!(decode("100%"), "100%");
+        assert!(parse_query("").is_empty());
+    }
+
+    #[test]
+    fn test_local_host_header() {
+        assert!(is_local_host_header("localhost", 7777));
+        assert!(is_local_host_header("localhost:7777", 7777));
+        assert!(is_local_host_header("127.0.0.1:7777", 7777));
+        assert!(is_local_host_header("[::1]", 7777));
+        assert!(is_local_host_header("[::1]:7777", 7777));
+
+        assert!(!is_local_host_header("localhost:8080", 7777));
+        assert!(!is_local_host_header("evil.example:7777", 7777));
+        assert!(!is_local_host_header("localhost.evil.example", 7777));
+    }
+}
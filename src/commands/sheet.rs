@@ -1,7 +1,12 @@
 use anyhow::Result;
 
 use crate::{
-    database::update_sheet_name,
+    binding::Binding,
+    database::{
+        get_all_sheets, is_sheet_archived, running_entry, set_sheet_archived, sheet_exists,
+        update_sheet_name,
+    },
+    hooks::{Hook, PendingHook},
     style::{style_string, Styles},
     State,
 };
@@ -32,16 +37,123 @@ pub fn checkout_sheet(name: &str, state: &mut State) -> Result<()> {
         name
     );
 
+    if is_sheet_archived(&name, &state.database)? {
+        println!(
+            "{}",
+            style_string("This sheet is archived, so its tasks can only be read.", Styles::Message)
+        );
+    }
+
+    // The hook gets the task running on the new sheet, if there is one
+    let entry = running_entry(&state.database, &name)?;
+    state.pending_hooks.push(PendingHook {
+        hook: Hook::PostSwitch,
+        sheet: name,
+        entry,
+    });
+
+    Ok(())
+}
+
+pub fn archive_sheet(name: &str, archive: bool, state: &State) -> Result<()> {
+    let sheets = get_all_sheets(&state.database)?;
+
+    // Guard for non-existent sheets
+    if !sheets.iter().any(|s| s == name) {
+        println!(
+            "{} {}",
+            style_string("Sheet not found:", Styles::Message),
+            name
+        );
+        return Ok(());
+    }
+
+    if archive && running_entry(&state.database, name)?.is_some() {
+        println!(
+            "{}",
+            style_string("Cannot archive a sheet with a running task. Check out first.", Styles::Message)
+        );
+        return Ok(());
+    }
+
+    set_sheet_archived(name, archive, &state.database)?;
+
+    let message = match archive {
+        true => "Archived sheet:",
+        false => "Unarchived sheet:",
+    };
+
+    println!("{} {}", style_string(message, Styles::Message), name);
+
+    Ok(())
+}
+
+// Archived sheets can't be changed, unless forced. Prints the reason and returns
+// true if the change should not happen.
+pub fn is_read_only(sheet: &str, force: &bool, state: &State) -> Result<bool> {
+    if *force || !is_sheet_archived(sheet, &state.database)? {
+        return Ok(false);
+    }
+
+    print_archived(sheet);
+
+    Ok(true)
+}
+
+pub fn print_archived(sheet: &str) {
+    println!(
+        "{} {}",
+        style_string("The sheet is archived:", Styles::Message),
+        sheet
+    );
+    println!(
+        "{}",
+        style_string("Use --force to change it anyway, or unarchive it first.", Styles::Message)
+    );
+}
+
+pub fn bind_sheet(name: &str, state: &mut State) -> Result<()> {
+    // Keep the default tags if the directory was already bound
+    let tags = match &state.binding {
+        Some(binding) => binding.tags.clone(),
+        None => Vec::new(),
+    };
+
+    let binding = Binding::write(name, &tags)?;
+
+    println!(
+        "{} {} {}",
+        style_string("Bound sheet", Styles::Message),
+        name,
+        style_string(&format!("to {}", binding.path.display()), Styles::Message)
+    );
+
+    state.binding = Some(binding);
+
     Ok(())
 }
 
 pub fn rename_sheet(name: &str, new_name: &str, state: &mut State) -> Result<()> {
+    // Renaming onto another sheet would mix their tasks, and drop its archived state
+    if sheet_exists(new_name, &state.database)? {
+        println!(
+            "{} {}",
+            style_string("A sheet with this name already exists:", Styles::Message),
+            new_name
+        );
+        return Ok(());
+    }
+
     update_sheet_name(name, new_name, &state.database)?;
 
     if state.current_sheet == name {
         state.update_sheet_name(new_name)?;
     }
 
+    if state.last_sheet == name {
+        state.update_last_sheet(new_name)?;
+    }
+
     println!(
         "{}",
         style_string("Sheet renamed succesfully.", Styles::Message)
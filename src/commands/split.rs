@@ -0,0 +1,160 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+
+use crate::commands::display::{print_all_tasks_readable, ReadableOptions};
+use crate::commands::sheet::is_read_only;
+use crate::database::{create_entry, get_entry_by_id, running_entry, update_entry};
+use crate::style::{style_string, Styles};
+use crate::Entry;
+use crate::State;
+
+pub fn split_task(
+    id: &Option<usize>,
+    at: DateTime<Local>,
+    note: Option<&String>,
+    force: &bool,
+    state: &State,
+) -> Result<()> {
+    // Without an id, the running task is split
+    let entry = match id {
+        Some(id) => get_entry_by_id(id, &state.database)?,
+        None => running_entry(&state.database, state.active_sheet())?,
+    };
+
+    let Some(entry) = entry else {
+        println!(
+            "{}",
+            style_string("The task was not found. Either the given id is invalid or there is no task running.", Styles::Message)
+        );
+        return Ok(());
+    };
+
+    if is_read_only(&entry.sheet, force, state)? {
+        return Ok(());
+    }
+
+    if let Some(problem) = split_problem(&entry, at) {
+        println!("{}", style_string(&problem, Styles::Message));
+        return Ok(());
+    }
+
+    let (entry, second) = split_entry(entry, at, note, state)?;
+
+    // Display output
+    println!("{}", style_string("Task split in:", Styles::Message));
+
+    let mut options = ReadableOptions::new();
+    options.show_headings = true;
+    options.show_ids = true;
+
+    print_all_tasks_readable("", &vec![entry, second], &options);
+
+    Ok(())
+}
+
+// The split point must fall strictly inside the task
+pub fn split_problem(entry: &Entry, at: DateTime<Local>) -> Option<String> {
+    let end = entry.end.unwrap_or(Local::now());
+
+    if at > entry.start && at < end {
+        return None;
+    }
+
+    Some(format!(
+        "The split point must be inside the task: {} - {}",
+        entry.start.format("%Y-%m-%d %H:%M:%S"),
+        end.format("%Y-%m-%d %H:%M:%S")
+    ))
+}
+
+// Stops the entry at the given time, and creates the second part from there
+pub fn split_entry(
+    mut entry: Entry,
+    at: DateTime<Local>,
+    note: Option<&String>,
+    state: &State,
+) -> Result<(Entry, Entry)> {
+    // The second part keeps running if the original task was running
+    let mut second = Entry::start(note.unwrap_or(&entry.name), &entry.sheet, at);
+    second.end = entry.end;
+    second.tags = entry.tags.clone();
+
+    // The original task must be stopped first, since a sheet can only have one running task
+    entry.stop(at);
+    update_entry(&entry, &state.database)?;
+
+    create_entry(&second, &state.database)?;
+    second.id = Some(state.database.last_insert_rowid() as usize);
+
+    Ok((entry, second))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_tables, running_entries};
+    use crate::hooks::HookOutput;
+    use chrono::{Duration, TimeZone};
+    use rusqlite::Connection;
+
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap()
+    }
+
+    fn state() -> State {
+        let database = Connection::open_in_memory().unwrap();
+        create_tables(&database).unwrap();
+
+        State {
+            current_sheet: "default".to_string(),
+            last_sheet: "default".to_string(),
+            binding: None,
+            database,
+            pending_hooks: Vec::new(),
+            hook_output: HookOutput::Inherit,
+        }
+    }
+
+    #[test]
+    fn test_split_point_outside_the_task() {
+        let mut entry = Entry::start("coding", "default", at(9, 0));
+        entry.end = Some(at(10, 0));
+
+        assert!(split_problem(&entry, at(9, 30)).is_none());
+        assert!(split_problem(&entry, at(8, 59)).is_some());
+        assert!(split_problem(&entry, at(10, 1)).is_some());
+
+        // The bounds would leave an empty part
+        assert!(split_problem(&entry, at(9, 0)).is_some());
+        assert!(split_problem(&entry, at(10, 0)).is_some());
+
+        // A running task can only be split in the past
+        entry.end = None;
+        assert!(split_problem(&entry, at(23, 0)).is_none());
+        assert!(split_problem(&entry, Local::now() + Duration::minutes(5)).is_some());
+    }
+
+    #[test]
+    fn test_split_running_task() {
+        let state = state();
+
+        let mut entry = Entry::start("coding", "default", at(9, 0));
+        entry.tags = vec!["acme".to_string()];
+        create_entry(&entry, &state.database).unwrap();
+        entry.id = Some(state.database.last_insert_rowid() as usize);
+
+        let review = "review".to_string();
+        let (first, second) = split_entry(entry, at(9, 30), Some(&review), &state).unwrap();
+
+        assert_eq!(first.end, Some(at(9, 30)));
+        assert_eq!(second.start, at(9, 30));
+        assert_eq!(second.name, "review");
+        assert_eq!(second.tags, vec!["acme".to_string()]);
+
+        // Only the second part keeps running
+        let running = running_entries(&state.database).unwrap();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].id, second.id);
+        assert!(running[0].end.is_none());
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::database::running_entry;
+use crate::utils::{format_duration, time_from_now};
+use crate::Entry;
+use crate::State;
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum StatusFormat {
+    Plain,
+    Tmux,
+    Polybar,
+    Waybar,
+    I3blocks,
+}
+
+pub fn print_status(
+    format: &StatusFormat,
+    template: Option<&String>,
+    config: &Config,
+    state: &State,
+) -> Result<()> {
+    // Like the prompt, the sheet bound to the directory is shown
+    let sheet = state.active_sheet();
+    let entry = running_entry(&state.database, sheet)?;
+
+    let text = match &entry {
+        Some(e) => render_template(template.unwrap_or(&config.status_template), e, format),
+        None => config
+            .status_stopped_template
+            .replace("{sheet}", &escape(sheet, format)),
+    };
+
+    match format {
+        StatusFormat::Plain => println!("{}", text),
+        StatusFormat::Tmux => match entry {
+            Some(_) => println!("#[fg=green]{}#[default]", text),
+            None => println!("#[fg=colour244]{}#[default]", text),
+        },
+        StatusFormat::Polybar => println!("{}", polybar_text(&text, entry.is_some())),
+        StatusFormat::I3blocks => {
+            // i3blocks reads the full text, the short text and the color from separate lines
+            let short_text = match &entry {
+                Some(e) => format_duration(&time_from_now(&e.start)),
+                None => text.clone(),
+            };
+            let color = match entry {
+                Some(_) => "#00FF00",
+                None => "#888888",
+            };
+
+            println!("{}\n{}\n{}", text, short_text, color);
+        }
+        StatusFormat::Waybar => {
+            let (tooltip, class) = match &entry {
+                Some(e) => (
+                    format!(
+                        "Sheet: {}\nTask: {}\nStarted: {}\nElapsed: {}",
+                        e.sheet,
+                        e.name,
+                        e.start.format("%H:%M:%S"),
+                        format_duration(&time_from_now(&e.start))
+                    ),
+                    "running",
+                ),
+                None => (format!("Sheet: {}\nNo active task", sheet), "stopped"),
+            };
+
+            let output = json!({
+                "text": text,
+                "tooltip": tooltip,
+                "class": class,
+                "alt": class,
+            });
+
+            println!("{}", output);
+        }
+    };
+
+    Ok(())
+}
+
+// Available placeholders: {sheet}, {note}, {start}, {elapsed}
+fn render_template(template: &str, entry: &Entry, format: &StatusFormat) -> String {
+    template
+        .replace("{sheet}", &escape(&entry.sheet, format))
+        .replace("{note}", &escape(&entry.name, format))
+        .replace("{start}", &entry.start.format("%H:%M").to_string())
+        .replace("{elapsed}", &format_duration(&time_from_now(&entry.start)))
+        .trim()
+        .to_string()
+}
+
+// The sheets and notes can't be read as the formatting tags of the bar, like "#[" in tmux
+// or "%{" in polybar. The templates are kept as they are, to allow some formatting.
+fn escape(text: &str, format: &StatusFormat) -> String {
+    match format {
+        StatusFormat::Tmux => text.replace('#', "##"),
+        StatusFormat::Polybar => text.replace('%', "%%"),
+        _ => text.to_string(),
+    }
+}
+
+// Polybar colors the text with its own formatting tags
+fn polybar_text(text: &str, running: bool) -> String {
+    let color = match running {
+        true => "#00FF00",
+        false => "#888888",
+    };
+
+    format!("%{{F{}}}{}%{{F-}}", color, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Local};
+
+    #[test]
+    fn test_render_template() {
+        let start = Local::now() - Duration::minutes(83);
+        let entry = Entry::start("code review", "client", start);
+
+        let res = render_template("{sheet}: {note} {elapsed}", &entry, &StatusFormat::Plain);
+        assert!(res.starts_with("client: code review 1:23:"));
+
+        // The spacing of the template is kept, only the ends are trimmed
+        let entry = Entry::start("", "client", start);
+        let res = render_template(" {sheet}  |  {elapsed} {note}", &entry, &StatusFormat::Plain);
+        assert!(res.starts_with("client  |  1:23:"));
+        assert!(!res.ends_with(' '));
+    }
+
+    #[test]
+    fn test_escape_markup() {
+        let entry = Entry::start("fix #[bold] and 100%{F-}", "client", Local::now());
+
+        let res = render_template("#[fg=red]{note}", &entry, &StatusFormat::Tmux);
+        assert_eq!(res, "#[fg=red]fix ##[bold] and 100%{F-}");
+
+        let res = render_template("{note}", &entry, &StatusFormat::Polybar);
+        assert_eq!(res, "fix #[bold] and 100%%{F-}");
+
+        let res = render_template("{note}", &entry, &StatusFormat::Plain);
+        assert_eq!(res, "fix #[bold] and 100%{F-}");
+    }
+
+    #[test]
+    fn test_polybar_text() {
+        assert_eq!(polybar_text("client: 0:10:00", true), "%{F#00FF00}client: 0:10:00%{F-}");
+        assert_eq!(polybar_text("client: stopped", false), "%{F#888888}client: stopped%{F-}");
+    }
+}
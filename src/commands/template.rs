@@ -0,0 +1,156 @@
+use anyhow::Result;
+use chrono::Duration;
+use tabled::builder::Builder;
+use tabled::settings::object::Rows;
+use tabled::settings::themes::Colorization;
+use tabled::settings::{Color, Style};
+
+use crate::database::{delete_template, get_all_templates, get_template, write_template};
+use crate::style::{style_string, Styles};
+use crate::template::Template;
+use crate::utils::{format_duration, parse_duration};
+use crate::State;
+
+// The note, sheet and tags of a task to start or log. A note like "@standup" is
+// replaced by that template, whose sheet is used unless another one is given.
+pub struct TaskArgs {
+    pub note: String,
+    pub sheet: Option<String>,
+    pub tags: Vec<String>,
+    pub duration: Option<Duration>,
+}
+
+// None if the template doesn't exist, after telling the user
+pub fn expand_task(
+    task: &str,
+    sheet: Option<&String>,
+    tags: &[String],
+    state: &State,
+) -> Result<Option<TaskArgs>> {
+    let mut args = TaskArgs {
+        note: task.to_string(),
+        sheet: sheet.cloned(),
+        tags: tags.to_vec(),
+        duration: None,
+    };
+
+    let Some(name) = task.strip_prefix('@') else {
+        return Ok(Some(args));
+    };
+
+    let Some(template) = get_template(name, &state.database)? else {
+        println!(
+            "{} {}",
+            style_string("Template not found:", Styles::Message),
+            name
+        );
+        return Ok(None);
+    };
+
+    args.note = template.note;
+    args.sheet = args.sheet.or(template.sheet);
+    args.tags.extend(template.tags);
+    args.duration = template.duration;
+
+    Ok(Some(args))
+}
+
+pub fn add_template(
+    name: &str,
+    note: Option<&String>,
+    sheet: Option<&String>,
+    tags: &[String],
+    duration: Option<&String>,
+    state: &State,
+) -> Result<()> {
+    // The name can also be written as it's used, e.g. "@standup"
+    let name = name.strip_prefix('@').unwrap_or(name);
+
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        println!(
+            "{}",
+            style_string("The template name can't be empty or contain spaces.", Styles::Message)
+        );
+        return Ok(());
+    }
+
+    let mut template = Template {
+        name: name.to_string(),
+        note: note.cloned().unwrap_or(name.to_string()),
+        sheet: sheet.cloned(),
+        tags: tags.to_vec(),
+        duration: duration.map(|d| parse_duration(d)).transpose()?,
+    };
+    template.tags.sort();
+    template.tags.dedup();
+
+    let exists = get_template(name, &state.database)?.is_some();
+    write_template(&template, &state.database)?;
+
+    let message = match exists {
+        true => "Template updated:",
+        false => "Template saved:",
+    };
+
+    println!("{} {}", style_string(message, Styles::Message), name);
+
+    Ok(())
+}
+
+pub fn list_templates(state: &State) -> Result<()> {
+    let templates = get_all_templates(&state.database)?;
+
+    if templates.is_empty() {
+        println!("{}", style_string("There are no templates.", Styles::Message));
+        return Ok(());
+    }
+
+    println!("{}", style_string("Templates:", Styles::Title));
+
+    let mut builder = Builder::new();
+    builder.push_record(vec!["Name", "Sheet", "Duration", "Task"]);
+
+    for template in &templates {
+        let mut task = vec![template.note.clone()];
+        task.extend(template.tags.iter().map(|t| format!("#{}", t)));
+
+        builder.push_record(vec![
+            format!("@{}", template.name),
+            template.sheet.clone().unwrap_or_default(),
+            template.duration.map(|d| format_duration(&d)).unwrap_or_default(),
+            task.join(" "),
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.with(Colorization::exact([Color::BOLD], Rows::first()));
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+pub fn remove_template(name: &str, state: &State) -> Result<()> {
+    let name = name.strip_prefix('@').unwrap_or(name);
+
+    // Guard for non-existent templates
+    if get_template(name, &state.database)?.is_none() {
+        println!(
+            "{} {}",
+            style_string("Template not found:", Styles::Message),
+            name
+        );
+        return Ok(());
+    }
+
+    delete_template(name, &state.database)?;
+
+    println!(
+        "{} {}",
+        style_string("Template removed:", Styles::Message),
+        name
+    );
+
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveTime, Timelike};
+use colored::{Color, Colorize};
+use std::collections::BTreeMap;
+use tabled::builder::Builder;
+use tabled::settings::object::Rows;
+use tabled::settings::themes::Colorization;
+use tabled::settings::Style;
+
+use crate::chart::{terminal_width, use_unicode};
+use crate::config::Config;
+use crate::database::get_all_entries;
+use crate::style::{style_string, Styles};
+use crate::utils::{format_duration, local_datetime};
+use crate::Entry;
+use crate::State;
+
+// Red is left for the overlaps
+const SHEET_COLORS: [Color; 5] = [
+    Color::Green,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+];
+
+type Interval = (DateTime<Local>, DateTime<Local>);
+
+pub fn display_timeline(
+    day: Option<DateTime<Local>>,
+    full: &bool,
+    config: &Config,
+    state: &State,
+) -> Result<()> {
+    let day = day.unwrap_or(Local::now()).date_naive();
+    let day_start = local_datetime(day.and_time(NaiveTime::MIN));
+    let day_end = local_datetime((day + Duration::days(1)).and_time(NaiveTime::MIN));
+
+    // The tasks of every sheet that overlap the day, cut to it
+    let mut entries: Vec<Entry> = get_all_entries(&state.database)?
+        .into_iter()
+        .filter(|e| e.start < day_end && e.end.unwrap_or(Local::now()) > day_start)
+        .collect();
+    entries.sort_by_key(|e| e.start);
+
+    if entries.is_empty() {
+        println!(
+            "{} {}",
+            style_string("There are no tasks on", Styles::Message),
+            day.format("%a %b %d, %Y")
+        );
+        return Ok(());
+    }
+
+    let clip = |e: &Entry| -> Interval {
+        let end = e.end.unwrap_or(Local::now());
+        (e.start.max(day_start), end.min(day_end))
+    };
+
+    // The working hours, stretched to whole hours to include every task
+    let (from, to) = match full {
+        true => (day_start, day_end),
+        false => {
+            let (work_start, work_end) = config.working_hours;
+            let from = entries
+                .iter()
+                .map(|e| clip(e).0)
+                .chain([local_datetime(day.and_time(work_start))])
+                .min()
+                .unwrap();
+            let to = entries
+                .iter()
+                .map(|e| clip(e).1)
+                .chain([local_datetime(day.and_time(work_end))])
+                .max()
+                .unwrap();
+
+            (floor_hour(from), ceil_hour(to).min(day_end))
+        }
+    };
+
+    let mut sheets: BTreeMap<&str, Vec<Interval>> = BTreeMap::new();
+    for entry in &entries {
+        sheets.entry(&entry.sheet).or_default().push(clip(entry));
+    }
+
+    // The ruler takes all the space left by the sheet names
+    let label_width = sheets
+        .keys()
+        .map(|s| s.chars().count())
+        .chain(["overlap".len()])
+        .max()
+        .unwrap()
+        + 2;
+    let width = terminal_width().saturating_sub(label_width + 1).max(24);
+
+    let unicode = use_unicode();
+    let (block, empty, overlap) = match unicode {
+        true => ("█", "·", "▲"),
+        false => ("#", ".", "^"),
+    };
+
+    println!(
+        "{} {}",
+        style_string("Timeline of", Styles::Title),
+        day.format("%a %b %d, %Y")
+    );
+
+    let (labels, ticks) = ruler(from, to, width, unicode);
+    println!("{:label_width$}{}", "", labels);
+    println!("{:label_width$}{}", "", ticks);
+
+    for (i, (sheet, intervals)) in sheets.iter().enumerate() {
+        let color = SHEET_COLORS[i % SHEET_COLORS.len()];
+
+        let line: String = coverage(intervals, from, to, width)
+            .iter()
+            .map(|count| match count {
+                0 => empty.bright_black().to_string(),
+                _ => block.color(color).to_string(),
+            })
+            .collect();
+
+        println!("{:label_width$}{}", sheet, line);
+    }
+
+    // The moments tracked more than once, in the same sheet or in different ones
+    let all: Vec<Interval> = entries.iter().map(clip).collect();
+    let counts = coverage(&all, from, to, width);
+
+    if counts.iter().any(|c| *c > 1) {
+        let line: String = counts
+            .iter()
+            .map(|count| match count {
+                0 | 1 => " ".to_string(),
+                _ => overlap.red().to_string(),
+            })
+            .collect();
+
+        println!("{:label_width$}{}", "overlap", line);
+    }
+
+    println!();
+
+    let mut builder = Builder::new();
+    builder.push_record(vec!["ID", "Sheet", "Start", "End", "Duration", "Task"]);
+
+    for entry in &entries {
+        let end = match entry.end {
+            Some(d) => d.format("%H:%M").to_string(),
+            None => "".to_string(),
+        };
+
+        builder.push_record(vec![
+            entry.id.unwrap().to_string(),
+            entry.sheet.clone(),
+            entry.start.format("%H:%M").to_string(),
+            end,
+            format_duration(&entry.get_duration()),
+            entry.description(),
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.with(Colorization::exact([tabled::settings::Color::BOLD], Rows::first()));
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+// The hours above the timeline, spaced out so that they don't touch
+fn ruler(
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    width: usize,
+    unicode: bool,
+) -> (String, String) {
+    let minutes = (to - from).num_minutes().max(1) as f64;
+    let position =
+        |t: DateTime<Local>| ((t - from).num_minutes() as f64 / minutes * width as f64) as usize;
+
+    let hours = (to - from).num_hours().max(1) as usize;
+    let step = [1, 2, 3, 4, 6, 12]
+        .into_iter()
+        .find(|step| width / hours * step >= 3)
+        .unwrap_or(12);
+
+    let (tick, line) = match unicode {
+        true => ('┬', '─'),
+        false => ('+', '-'),
+    };
+
+    let mut labels = String::new();
+    let mut ticks: Vec<char> = vec![line; width];
+
+    let mut hour = from;
+    while hour < to {
+        let pos = position(hour);
+
+        let fits = labels.len() <= pos && pos + 2 <= width;
+
+        if (hour.hour() as usize).is_multiple_of(step) && fits {
+            labels.push_str(&" ".repeat(pos - labels.len()));
+            labels.push_str(&hour.format("%H").to_string());
+            ticks[pos] = tick;
+        }
+
+        hour += Duration::hours(1);
+    }
+
+    (labels, ticks.into_iter().collect())
+}
+
+// How many intervals cover each column of the timeline
+fn coverage(
+    intervals: &[Interval],
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    width: usize,
+) -> Vec<usize> {
+    let column = (to - from) / width as i32;
+
+    (0..width)
+        .map(|i| {
+            let start = from + column * i as i32;
+            let end = start + column;
+
+            intervals.iter().filter(|(s, e)| *s < end && *e > start).count()
+        })
+        .collect()
+}
+
+fn floor_hour(dt: DateTime<Local>) -> DateTime<Local> {
+    dt.with_minute(0).unwrap().with_second(0).unwrap().with_nanosecond(0).unwrap()
+}
+
+fn ceil_hour(dt: DateTime<Local>) -> DateTime<Local> {
+    match floor_hour(dt) == dt {
+        true => dt,
+        false => floor_hour(dt) + Duration::hours(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_coverage() {
+        let at = |h, m| Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+        let intervals = [(at(9, 0), at(10, 0)), (at(9, 30), at(11, 0))];
+
+        // One column for each half hour, from 8 to 12
+        let res = coverage(&intervals, at(8, 0), at(12, 0), 8);
+        assert_eq!(res, vec![0, 0, 1, 2, 1, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::fmt;
+
+use crate::commands::sheet::print_archived;
+use crate::config::Config;
+use crate::database::{is_sheet_archived, running_entry, write_entry};
+use crate::hooks::{run_pre_hook, Hook, HookOutput, PendingHook};
+use crate::style::{style_string, Styles};
+use crate::Entry;
+use crate::State;
+
+// Starting and stopping tasks, shared by the commands, the JSON API and the full-screen
+// interface, so that they follow the same rules and run the same hooks.
+
+// Why nothing was changed. Each interface reports it in its own way, unlike the errors
+// of the database.
+#[derive(Debug)]
+pub enum Refusal {
+    Archived(String),
+    AlreadyRunning(String),
+    NotRunning,
+    Problem(&'static str),
+    Hook(String),
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Refusal::Archived(sheet) => write!(f, "The sheet is archived: {}", sheet),
+            Refusal::AlreadyRunning(sheet) => write!(f, "Already checked into sheet: {}", sheet),
+            Refusal::NotRunning => write!(f, "There is no active task."),
+            Refusal::Problem(problem) => write!(f, "{}", problem),
+            Refusal::Hook(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Refusal {}
+
+// The task that was started or stopped, the one stopped to start it, and the post-hooks
+// to run once the changes are committed
+pub struct Changes {
+    pub entry: Entry,
+    pub stopped: Option<Entry>,
+    pub hooks: Vec<PendingHook>,
+}
+
+// Like `t in`: the running task is only stopped with `switch`
+#[allow(clippy::too_many_arguments)]
+pub fn start_entry(
+    task: &str,
+    sheet: &str,
+    start: DateTime<Local>,
+    switch: bool,
+    tags: &[String],
+    force: bool,
+    config: &Config,
+    output: HookOutput,
+    state: &State,
+) -> Result<Changes> {
+    ensure_writable(sheet, force, state)?;
+
+    let mut stopped = None;
+
+    if let Some(mut running) = running_entry(&state.database, sheet)? {
+        if !switch {
+            return Err(Refusal::AlreadyRunning(running.sheet).into());
+        }
+
+        if let Some(problem) = switch_problem(&running, start) {
+            return Err(Refusal::Problem(problem).into());
+        }
+
+        running.stop(start);
+        stopped = Some(running);
+    }
+
+    let mut entry = Entry::start(task, sheet, start);
+
+    // The default tags of a bound sheet are added to the given ones
+    if let Some(binding) = state.binding.as_ref().filter(|b| b.sheet == sheet) {
+        entry.tags.extend(binding.tags.iter().cloned());
+    }
+    entry.tags.extend(tags.iter().cloned());
+    entry.tags.sort();
+    entry.tags.dedup();
+
+    if let Some(stopped) = &stopped {
+        pre_hook(Hook::PreOut, stopped, config, output)?;
+    }
+    pre_hook(Hook::PreIn, &entry, config, output)?;
+
+    let mut hooks = Vec::new();
+
+    if let Some(stopped) = &stopped {
+        write_entry(stopped, &state.database)?;
+        hooks.push(post_hook(Hook::PostOut, stopped));
+    }
+
+    write_entry(&entry, &state.database)?;
+    entry.id = Some(state.database.last_insert_rowid() as usize);
+    hooks.push(post_hook(Hook::PostIn, &entry));
+
+    Ok(Changes {
+        entry,
+        stopped,
+        hooks,
+    })
+}
+
+// Like `t out`
+pub fn stop_entry(
+    sheet: &str,
+    end: DateTime<Local>,
+    force: bool,
+    config: &Config,
+    output: HookOutput,
+    state: &State,
+) -> Result<Changes> {
+    ensure_writable(sheet, force, state)?;
+
+    let Some(mut entry) = running_entry(&state.database, sheet)? else {
+        return Err(Refusal::NotRunning.into());
+    };
+
+    if let Some(problem) = stop_problem(&entry, end) {
+        return Err(Refusal::Problem(problem).into());
+    }
+
+    entry.stop(end);
+    pre_hook(Hook::PreOut, &entry, config, output)?;
+    write_entry(&entry, &state.database)?;
+
+    let hooks = vec![post_hook(Hook::PostOut, &entry)];
+
+    Ok(Changes {
+        entry,
+        stopped: None,
+        hooks,
+    })
+}
+
+// Archived sheets can't be changed, unless forced
+pub fn ensure_writable(sheet: &str, force: bool, state: &State) -> Result<()> {
+    match !force && is_sheet_archived(sheet, &state.database)? {
+        true => Err(Refusal::Archived(sheet.to_string()).into()),
+        false => Ok(()),
+    }
+}
+
+// The commands print why nothing was changed, like their other guards, but a pre-hook
+// that said no is an error
+pub fn print_refusal(err: anyhow::Error) -> Result<()> {
+    let Some(refusal) = err.downcast_ref::<Refusal>() else {
+        return Err(err);
+    };
+
+    match refusal {
+        Refusal::Hook(_) => return Err(err),
+        Refusal::Archived(sheet) => print_archived(sheet),
+        Refusal::AlreadyRunning(sheet) => println!(
+            "{} {}",
+            style_string("Already checked into sheet:", Styles::Message),
+            sheet
+        ),
+        _ => println!("{}", style_string(&refusal.to_string(), Styles::Message)),
+    };
+
+    Ok(())
+}
+
+// Why the running task can't be stopped to start a new one, if it can't
+fn switch_problem(running: &Entry, start: DateTime<Local>) -> Option<&'static str> {
+    match running.start > start {
+        true => Some("Cannot switch to the new task, because it would stop the previous task before it was started."),
+        false => None,
+    }
+}
+
+// Why a task can't be stopped at the given time, if it can't
+fn stop_problem(entry: &Entry, end: DateTime<Local>) -> Option<&'static str> {
+    match entry.start > end {
+        true => Some("Cannot stop a task before it started."),
+        false => None,
+    }
+}
+
+fn pre_hook(hook: Hook, entry: &Entry, config: &Config, output: HookOutput) -> Result<()> {
+    run_pre_hook(hook, &entry.sheet, Some(entry), config, output)
+        .map_err(|e| Refusal::Hook(format!("{:#}", e)).into())
+}
+
+fn post_hook(hook: Hook, entry: &Entry) -> PendingHook {
+    PendingHook {
+        hook,
+        sheet: entry.sheet.clone(),
+        entry: Some(entry.clone()),
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use chrono::Local;
+use tabled::builder::Builder;
+use tabled::settings::object::Rows;
+use tabled::settings::themes::Colorization;
+use tabled::settings::{Color, Style};
+
+use crate::commands::sheet::is_read_only;
+use crate::database::{
+    get_deleted_entries, get_deleted_ids, purge_entry_by_id, restore_entry_by_id, running_entry,
+};
+use crate::style::{style_string, Styles};
+use crate::utils::{confirm_action, format_duration, parse_duration};
+use crate::State;
+
+pub fn list_trash(state: &State) -> Result<()> {
+    let mut entries = get_deleted_entries(&state.database)?;
+    let unreadable = get_deleted_ids(&state.database)?.len() - entries.len();
+
+    if entries.is_empty() && unreadable == 0 {
+        println!("{}", style_string("The trash is empty.", Styles::Message));
+        return Ok(());
+    }
+
+    entries.sort_by_key(|(_, deleted_at)| *deleted_at);
+
+    println!("{}", style_string("Trash:", Styles::Title));
+
+    let mut builder = Builder::new();
+    builder.push_record(vec!["ID", "Deleted", "Sheet", "Date", "Duration", "Task"]);
+
+    for (entry, deleted_at) in &entries {
+        builder.push_record(vec![
+            entry.id.unwrap().to_string(),
+            deleted_at.format("%a %b %d, %Y %H:%M").to_string(),
+            entry.sheet.clone(),
+            entry.start.format("%a %b %d, %Y %H:%M").to_string(),
+            format_duration(&entry.get_duration()),
+            entry.description(),
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.with(Colorization::exact([Color::BOLD], Rows::first()));
+
+    if !entries.is_empty() {
+        println!("{}", table);
+    }
+
+    if unreadable > 0 {
+        println!(
+            "{} {}",
+            style_string("Unreadable entries, which can only be purged:", Styles::Message),
+            unreadable
+        );
+    }
+
+    Ok(())
+}
+
+pub fn restore_from_trash(id: &usize, force: &bool, state: &State) -> Result<()> {
+    let entries = get_deleted_entries(&state.database)?;
+
+    // Guard for entries that are not in the trash
+    let Some((entry, _)) = entries.iter().find(|(e, _)| e.id == Some(*id)) else {
+        println!(
+            "{} {}",
+            style_string("Entry not found in the trash. Id:", Styles::Message),
+            id
+        );
+        return Ok(());
+    };
+
+    if is_read_only(&entry.sheet, force, state)? {
+        return Ok(());
+    }
+
+    // A running entry can only come back if its sheet has no other running task
+    if entry.end.is_none() && running_entry(&state.database, &entry.sheet)?.is_some() {
+        println!(
+            "{} {}",
+            style_string("Cannot restore a running task, because another one is running in sheet:", Styles::Message),
+            entry.sheet
+        );
+        return Ok(());
+    }
+
+    restore_entry_by_id(id, &state.database)?;
+
+    println!(
+        "{} {}",
+        style_string("Restored entry:", Styles::Message),
+        id
+    );
+
+    Ok(())
+}
+
+pub fn purge_trash(older_than: Option<&String>, yes: &bool, state: &State) -> Result<()> {
+    let mut entries = get_deleted_ids(&state.database)?;
+
+    if let Some(older_than) = older_than {
+        let limit = Local::now() - parse_duration(older_than)?;
+        entries.retain(|(_, deleted_at)| *deleted_at < limit);
+    }
+
+    if entries.is_empty() {
+        println!(
+            "{}",
+            style_string("There is nothing to purge.", Styles::Message)
+        );
+        return Ok(());
+    }
+
+    if !yes
+        && !confirm_action(&format!(
+            "Are you sure you want to permanently remove {} entries (y/N)?",
+            entries.len()
+        ))
+    {
+        return Ok(());
+    }
+
+    for (id, _) in &entries {
+        purge_entry_by_id(id, &state.database)?;
+    }
+
+    println!(
+        "{} {}",
+        style_string("Entries removed permanently:", Styles::Message),
+        entries.len()
+    );
+
+    Ok(())
+}
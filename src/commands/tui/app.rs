@@ -0,0 +1,383 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local};
+use langtime::parse;
+use ratatui::widgets::{ListState, TableState};
+
+use crate::commands::merge::{merge_entries, merge_problem};
+use crate::commands::split::{split_entry, split_problem};
+use crate::commands::timer::{ensure_writable, start_entry, stop_entry};
+use crate::config::Config;
+use crate::database::{
+    begin_transaction, commit_transaction, get_all_entries, get_all_sheets, get_archived_sheets,
+    get_sheet_entries, remove_entry_by_id, rollback_transaction, running_entries, update_entry,
+};
+use crate::hooks::{post_hook_problems, Hook, HookOutput, PendingHook};
+use crate::Entry;
+use crate::State;
+
+pub const FIELDS: [&str; 4] = ["Start", "End", "Note", "Sheet"];
+
+#[derive(PartialEq)]
+pub enum Focus {
+    Sheets,
+    Tasks,
+}
+
+pub enum Mode {
+    Normal,
+    Edit(EditForm),
+    Prompt(Prompt, String),
+    ConfirmDelete,
+}
+
+#[derive(Clone, Copy)]
+pub enum Prompt {
+    CheckIn,
+    Split,
+}
+
+impl Prompt {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Prompt::CheckIn => "Check in with the task description",
+            Prompt::Split => "Split the task at (e.g. \"10:30\", \"15 minutes ago\")",
+        }
+    }
+}
+
+// The values of the task being edited, as they are typed
+pub struct EditForm {
+    pub id: usize,
+    pub values: [String; 4],
+    pub field: usize,
+}
+
+pub struct App {
+    pub sheets: Vec<String>,
+    pub archived: Vec<String>,
+    pub running: Vec<String>,
+    pub sheet_state: ListState,
+    pub entries: Vec<Entry>,
+    pub entry_state: TableState,
+    pub today: Vec<Entry>,
+    pub marked: Vec<usize>,
+    pub focus: Focus,
+    pub mode: Mode,
+    pub message: Option<(String, bool)>,
+    pub quit: bool,
+    // The post-hooks of the change being applied
+    pending_hooks: Vec<PendingHook>,
+}
+
+impl App {
+    pub fn new(state: &State) -> Result<App> {
+        let mut app = App {
+            sheets: Vec::new(),
+            archived: Vec::new(),
+            running: Vec::new(),
+            sheet_state: ListState::default(),
+            entries: Vec::new(),
+            entry_state: TableState::default(),
+            today: Vec::new(),
+            marked: Vec::new(),
+            focus: Focus::Tasks,
+            mode: Mode::Normal,
+            message: None,
+            quit: false,
+            pending_hooks: Vec::new(),
+        };
+
+        app.load_sheets(state)?;
+
+        // Start from the active sheet
+        let active = app.sheets.iter().position(|s| s == state.active_sheet());
+        app.sheet_state.select(active.or(Some(0)));
+        app.load_entries(state)?;
+
+        Ok(app)
+    }
+
+    pub fn sheet(&self) -> &str {
+        &self.sheets[self.sheet_state.selected().unwrap_or(0)]
+    }
+
+    pub fn entry(&self) -> Option<&Entry> {
+        self.entry_state.selected().and_then(|i| self.entries.get(i))
+    }
+
+    pub fn today_total(&self) -> Duration {
+        self.today.iter().map(|e| e.get_duration()).sum()
+    }
+
+    // Reads everything again, keeping the selection where possible
+    pub fn reload(&mut self, state: &State) -> Result<()> {
+        let sheet = self.sheet().to_string();
+        let id = self.entry().and_then(|e| e.id);
+
+        self.load_sheets(state)?;
+        let index = self.sheets.iter().position(|s| *s == sheet);
+        self.sheet_state.select(index.or(Some(0)));
+
+        self.load_entries(state)?;
+        if let Some(index) = self.entries.iter().position(|e| e.id == id) {
+            self.entry_state.select(Some(index));
+        }
+
+        Ok(())
+    }
+
+    fn load_sheets(&mut self, state: &State) -> Result<()> {
+        let mut sheets = get_all_sheets(&state.database)?;
+
+        // The active sheet is shown even before its first task
+        if !sheets.iter().any(|s| s == state.active_sheet()) {
+            sheets.push(state.active_sheet().to_string());
+        }
+        sheets.sort();
+
+        self.sheets = sheets;
+        self.archived = get_archived_sheets(&state.database)?;
+        self.running = running_entries(&state.database)?
+            .into_iter()
+            .map(|e| e.sheet)
+            .collect();
+
+        let today = Local::now().date_naive();
+        self.today = get_all_entries(&state.database)?
+            .into_iter()
+            .filter(|e| e.start.date_naive() == today)
+            .collect();
+
+        Ok(())
+    }
+
+    // The tasks of the selected sheet, with the last one selected
+    fn load_entries(&mut self, state: &State) -> Result<()> {
+        let mut entries = get_sheet_entries(self.sheet(), &state.database)?;
+        entries.sort_by_key(|e| e.start);
+
+        self.entry_state.select(entries.len().checked_sub(1));
+        self.entries = entries;
+        self.marked.clear();
+
+        Ok(())
+    }
+
+    pub fn move_selection(&mut self, offset: isize, state: &State) -> Result<()> {
+        match self.focus {
+            Focus::Sheets => {
+                let index = step(self.sheet_state.selected(), offset, self.sheets.len());
+                self.sheet_state.select(index);
+                self.load_entries(state)?;
+            }
+            Focus::Tasks => {
+                let index = step(self.entry_state.selected(), offset, self.entries.len());
+                self.entry_state.select(index);
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.entry().and_then(|e| e.id) else {
+            return;
+        };
+
+        match self.marked.contains(&id) {
+            true => self.marked.retain(|m| *m != id),
+            false => self.marked.push(id),
+        };
+    }
+
+    pub fn start_edit(&mut self) {
+        let Some(entry) = self.entry() else {
+            return;
+        };
+
+        let end = match entry.end {
+            Some(end) => format_time(end),
+            None => "".to_string(),
+        };
+
+        self.mode = Mode::Edit(EditForm {
+            id: entry.id.unwrap(),
+            values: [
+                format_time(entry.start),
+                end,
+                entry.name.clone(),
+                entry.sheet.clone(),
+            ],
+            field: 0,
+        });
+    }
+
+    // Runs a change in a transaction, then shows its outcome and the new data.
+    // Checking in and out run the pre-hooks first, so the lock is taken by the first change.
+    pub fn apply(
+        &mut self,
+        config: &Config,
+        state: &State,
+        change: impl FnOnce(&mut App) -> Result<String>,
+    ) {
+        let res = begin_transaction(&state.database, false).and_then(|_| change(self));
+        let hooks = std::mem::take(&mut self.pending_hooks);
+
+        let res = match res {
+            Ok(message) => commit_transaction(&state.database).map(|_| message),
+            Err(e) => {
+                let _ = rollback_transaction(&state.database);
+                Err(e)
+            }
+        };
+
+        self.message = Some(match res {
+            Ok(message) => {
+                let problems = post_hook_problems(&hooks, config, HookOutput::Discard);
+
+                match problems.is_empty() {
+                    true => (message, false),
+                    false => (format!("{} Warning: {}", message, problems.join(", ")), true),
+                }
+            }
+            Err(e) => (format!("{:#}", e), true),
+        });
+
+        if let Err(e) = self.reload(state) {
+            self.message = Some((format!("{:#}", e), true));
+        }
+    }
+
+    // Like `t in --switch`, the running task is stopped first
+    pub fn check_in(&mut self, note: &str, config: &Config, state: &State) -> Result<String> {
+        let sheet = self.sheet().to_string();
+        let now = Local::now();
+
+        let changes =
+            start_entry(note, &sheet, now, true, &[], false, config, HookOutput::Discard, state)?;
+        self.pending_hooks.extend(changes.hooks);
+
+        Ok(format!("Checked into sheet: {}", sheet))
+    }
+
+    pub fn check_out(&mut self, config: &Config, state: &State) -> Result<String> {
+        let sheet = self.sheet().to_string();
+
+        let changes = stop_entry(&sheet, Local::now(), false, config, HookOutput::Discard, state)?;
+        self.pending_hooks.extend(changes.hooks);
+
+        Ok(format!("Checked out of sheet: {}", sheet))
+    }
+
+    pub fn save_edit(&mut self, form: &EditForm, state: &State) -> Result<String> {
+        let Some(mut entry) = self.entries.iter().find(|e| e.id == Some(form.id)).cloned() else {
+            return Err(anyhow!("The task was not found."));
+        };
+
+        let [start, end, note, sheet] = &form.values;
+
+        if sheet.trim().is_empty() {
+            return Err(anyhow!("The sheet can't be empty."));
+        }
+
+        // Both the sheet the task is in and the one it is moved to must be writable
+        ensure_writable(&entry.sheet, false, state)?;
+        ensure_writable(sheet.trim(), false, state)?;
+
+        // The times are only parsed when they were changed, to keep their precision
+        if *start != format_time(entry.start) {
+            entry.start = parse(start)?;
+        }
+
+        entry.end = match (end.trim(), entry.end) {
+            ("", None) => None,
+            ("", Some(_)) => return Err(anyhow!("The end of a stopped task can't be removed.")),
+            (end, Some(old)) if end == format_time(old) => Some(old),
+            (end, _) => Some(parse(end)?),
+        };
+        entry.name = note.to_string();
+        entry.sheet = sheet.trim().to_string();
+
+        update_entry(&entry, &state.database)?;
+
+        self.pending_hooks.push(PendingHook {
+            hook: Hook::PostEdit,
+            sheet: entry.sheet.clone(),
+            entry: Some(entry),
+        });
+
+        Ok(format!("Entry updated: {}", form.id))
+    }
+
+    pub fn split(&mut self, at: &str, state: &State) -> Result<String> {
+        let Some(entry) = self.entry().cloned() else {
+            return Err(anyhow!("There is no task to split."));
+        };
+        ensure_writable(&entry.sheet, false, state)?;
+
+        let at = parse(at)?;
+        if let Some(problem) = split_problem(&entry, at) {
+            return Err(anyhow!(problem));
+        }
+
+        let (_, second) = split_entry(entry, at, None, state)?;
+
+        Ok(format!("Task split, the second part is: {}", second.id.unwrap()))
+    }
+
+    pub fn merge(&mut self, state: &State) -> Result<String> {
+        let entries: Vec<Entry> = self
+            .entries
+            .iter()
+            .filter(|e| e.id.is_some_and(|id| self.marked.contains(&id)))
+            .cloned()
+            .collect();
+
+        if let Some(problem) = merge_problem(&entries) {
+            return Err(anyhow!(problem));
+        }
+        ensure_writable(&entries[0].sheet, false, state)?;
+
+        let merged = merge_entries(&entries, state)?;
+
+        Ok(format!("Tasks merged in: {}", merged.id.unwrap()))
+    }
+
+    pub fn delete(&mut self, state: &State) -> Result<String> {
+        let Some(entry) = self.entry().cloned() else {
+            return Err(anyhow!("There is no task to remove."));
+        };
+        ensure_writable(&entry.sheet, false, state)?;
+
+        let id = entry.id.unwrap();
+        remove_entry_by_id(&id, &state.database)?;
+
+        Ok(format!("Moved entry to the trash: {}", id))
+    }
+}
+
+fn format_time(dt: DateTime<Local>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn step(selected: Option<usize>, offset: isize, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+
+    let index = selected.unwrap_or(0) as isize + offset;
+    Some(index.clamp(0, len as isize - 1) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        assert_eq!(step(Some(2), 1, 5), Some(3));
+        assert_eq!(step(Some(4), 10, 5), Some(4));
+        assert_eq!(step(Some(1), -10, 5), Some(0));
+        assert_eq!(step(None, 1, 0), None);
+    }
+}
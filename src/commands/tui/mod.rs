@@ -0,0 +1,149 @@
+mod app;
+mod ui;
+
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::ExecutableCommand;
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+use std::io::{stdout, IsTerminal};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::style::{style_string, Styles};
+use crate::State;
+use app::{App, Focus, Mode, Prompt, FIELDS};
+
+pub fn run_tui(config: &Config, state: &State) -> Result<()> {
+    if !stdout().is_terminal() {
+        println!(
+            "{}",
+            style_string("The full-screen interface needs a terminal.", Styles::Message)
+        );
+        return Ok(());
+    }
+
+    let mut app = App::new(state)?;
+
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+
+    let res = run_app(&mut app, config, state);
+
+    // The terminal is given back even when something went wrong
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+
+    res
+}
+
+fn run_app(app: &mut App, config: &Config, state: &State) -> Result<()> {
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    while !app.quit {
+        terminal.draw(|f| ui::draw(f, app))?;
+
+        // Redrawn every second, to keep the running tasks up to date
+        if !event::poll(Duration::from_secs(1))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                handle_key(key, app, config, state)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_key(key: KeyEvent, app: &mut App, config: &Config, state: &State) -> Result<()> {
+    let mode = std::mem::replace(&mut app.mode, Mode::Normal);
+
+    match mode {
+        Mode::Normal => handle_normal_key(key, app, config, state)?,
+        Mode::Edit(mut form) => match key.code {
+            KeyCode::Esc => {}
+            KeyCode::Enter => app.apply(config, state, |app| app.save_edit(&form, state)),
+            KeyCode::Tab | KeyCode::Down => {
+                form.field = (form.field + 1) % FIELDS.len();
+                app.mode = Mode::Edit(form);
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                form.field = (form.field + FIELDS.len() - 1) % FIELDS.len();
+                app.mode = Mode::Edit(form);
+            }
+            KeyCode::Backspace => {
+                form.values[form.field].pop();
+                app.mode = Mode::Edit(form);
+            }
+            KeyCode::Char(c) => {
+                form.values[form.field].push(c);
+                app.mode = Mode::Edit(form);
+            }
+            _ => app.mode = Mode::Edit(form),
+        },
+        Mode::Prompt(prompt, mut input) => match key.code {
+            KeyCode::Esc => {}
+            KeyCode::Enter => match prompt {
+                Prompt::CheckIn => {
+                    app.apply(config, state, |app| app.check_in(&input, config, state))
+                }
+                Prompt::Split => app.apply(config, state, |app| app.split(&input, state)),
+            },
+            KeyCode::Backspace => {
+                input.pop();
+                app.mode = Mode::Prompt(prompt, input);
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                app.mode = Mode::Prompt(prompt, input);
+            }
+            _ => app.mode = Mode::Prompt(prompt, input),
+        },
+        Mode::ConfirmDelete => {
+            if let KeyCode::Char('y') | KeyCode::Char('Y') = key.code {
+                app.apply(config, state, |app| app.delete(state));
+            }
+        }
+    };
+
+    Ok(())
+}
+
+fn handle_normal_key(key: KeyEvent, app: &mut App, config: &Config, state: &State) -> Result<()> {
+    let has_entry = app.entry().is_some();
+
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.quit = true,
+        KeyCode::Tab | KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
+            app.focus = match app.focus {
+                Focus::Sheets => Focus::Tasks,
+                Focus::Tasks => Focus::Sheets,
+            };
+        }
+        KeyCode::Up | KeyCode::Char('k') => app.move_selection(-1, state)?,
+        KeyCode::Down | KeyCode::Char('j') => app.move_selection(1, state)?,
+        KeyCode::PageUp => app.move_selection(-10, state)?,
+        KeyCode::PageDown => app.move_selection(10, state)?,
+        KeyCode::Enter if app.focus == Focus::Sheets => app.focus = Focus::Tasks,
+        KeyCode::Enter | KeyCode::Char('e') if has_entry => app.start_edit(),
+        KeyCode::Char('s') if has_entry => app.mode = Mode::Prompt(Prompt::Split, String::new()),
+        KeyCode::Char(' ') => app.toggle_mark(),
+        KeyCode::Char('m') => app.apply(config, state, |app| app.merge(state)),
+        KeyCode::Char('d') if has_entry => app.mode = Mode::ConfirmDelete,
+        KeyCode::Char('i') => app.mode = Mode::Prompt(Prompt::CheckIn, String::new()),
+        KeyCode::Char('o') => app.apply(config, state, |app| app.check_out(config, state)),
+        KeyCode::Char('r') => {
+            app.reload(state)?;
+            app.message = None;
+        }
+        _ => {}
+    };
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use chrono::{Duration, NaiveDate};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table};
+use ratatui::Frame;
+
+use crate::commands::tui::app::{App, Focus, Mode, FIELDS};
+use crate::utils::{duration_by_day, format_duration};
+
+const HELP: &str = "q quit  tab switch pane  i check in  o check out  e edit  s split  \
+                    space mark  m merge marked  d delete  r reload";
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let [main, status, help] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(f.size());
+
+    let [sheets, tasks] =
+        Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(main);
+
+    draw_sheets(f, app, sheets);
+    draw_tasks(f, app, tasks);
+    draw_status(f, app, status);
+
+    f.render_widget(
+        Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)),
+        help,
+    );
+
+    match &app.mode {
+        Mode::Normal => {}
+        Mode::Edit(form) => {
+            let lines: Vec<Line> = FIELDS
+                .iter()
+                .zip(&form.values)
+                .enumerate()
+                .map(|(i, (name, value))| {
+                    let style = match i == form.field {
+                        true => Style::default().add_modifier(Modifier::REVERSED),
+                        false => Style::default(),
+                    };
+                    Line::from(vec![
+                        Span::raw(format!("{:>6}: ", name)),
+                        Span::styled(format!("{}_", value), style),
+                    ])
+                })
+                .collect();
+
+            let title = format!(" Edit task {} (tab next field, enter save, esc cancel) ", form.id);
+            draw_popup(f, &title, lines, 6);
+        }
+        Mode::Prompt(prompt, input) => {
+            let title = format!(" {} ", prompt.title());
+            draw_popup(f, &title, vec![Line::from(format!("{}_", input))], 3);
+        }
+        Mode::ConfirmDelete => {
+            let line = match app.entry() {
+                Some(entry) => format!("Move task {} to the trash? (y/N)", entry.id.unwrap()),
+                None => "There is no task to remove.".to_string(),
+            };
+            draw_popup(f, " Delete ", vec![Line::from(line)], 3);
+        }
+    };
+}
+
+fn draw_sheets(f: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .sheets
+        .iter()
+        .map(|sheet| {
+            let mut spans = vec![Span::raw(sheet.clone())];
+
+            if app.running.contains(sheet) {
+                spans.push(Span::styled(" ●", Style::default().fg(Color::Green)));
+            }
+
+            if app.archived.contains(sheet) {
+                spans.push(Span::styled(" (archived)", Style::default().fg(Color::DarkGray)));
+            }
+
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(pane("Sheets", app.focus == Focus::Sheets))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    f.render_stateful_widget(list, area, &mut app.sheet_state);
+}
+
+// The tasks grouped by day, like the display command
+fn draw_tasks(f: &mut Frame, app: &mut App, area: Rect) {
+    let by_day = duration_by_day(&app.entries);
+    let mut previous: Option<NaiveDate> = None;
+
+    let rows: Vec<Row> = app
+        .entries
+        .iter()
+        .map(|entry| {
+            let day = entry.start.date_naive();
+
+            // The date and the time of the day are only shown on its first task
+            let (date, total) = match previous == Some(day) {
+                true => ("".to_string(), "".to_string()),
+                false => (
+                    entry.start.format("%a %b %d, %Y").to_string(),
+                    format_duration(by_day.get(&day).unwrap_or(&Duration::zero())),
+                ),
+            };
+            previous = Some(day);
+
+            let id = entry.id.unwrap();
+            let mark = match app.marked.contains(&id) {
+                true => "*",
+                false => " ",
+            };
+            let end = match entry.end {
+                Some(end) => end.format("%H:%M:%S").to_string(),
+                None => "".to_string(),
+            };
+
+            Row::new(vec![
+                Cell::from(format!("{}{}", mark, id)),
+                Cell::from(date),
+                Cell::from(entry.start.format("%H:%M:%S").to_string()),
+                Cell::from(end),
+                Cell::from(format_duration(&entry.get_duration())),
+                Cell::from(total).style(Style::default().fg(Color::DarkGray)),
+                Cell::from(entry.description()),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(6),
+        Constraint::Length(16),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Min(10),
+    ];
+
+    let header = Row::new(vec!["ID", "Date", "Start", "End", "Duration", "Day", "Task"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let title = format!("Tasks: {}", app.sheet());
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(pane(&title, app.focus == Focus::Tasks))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    f.render_stateful_widget(table, area, &mut app.entry_state);
+}
+
+fn draw_status(f: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![
+        Span::styled("Today: ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(format_duration(&app.today_total())),
+    ];
+
+    if !app.marked.is_empty() {
+        spans.push(Span::raw(format!("  Marked: {}", app.marked.len())));
+    }
+
+    if let Some((message, is_error)) = &app.message {
+        let color = match is_error {
+            true => Color::Red,
+            false => Color::Green,
+        };
+        spans.push(Span::styled(format!("  {}", message), Style::default().fg(color)));
+    }
+
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn pane(title: &str, focused: bool) -> Block<'_> {
+    let style = match focused {
+        true => Style::default().fg(Color::Cyan),
+        false => Style::default(),
+    };
+
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(format!(" {} ", title))
+}
+
+// A box in the middle of the screen, over everything else
+fn draw_popup(f: &mut Frame, title: &str, lines: Vec<Line>, height: u16) {
+    let area = f.size();
+    let width = area.width.saturating_sub(8).min(80);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height: height.min(area.height),
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title.to_string());
+
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
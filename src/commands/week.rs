@@ -0,0 +1,113 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use std::collections::BTreeMap;
+use tabled::builder::Builder;
+use tabled::settings::object::{Columns, Rows};
+use tabled::settings::themes::Colorization;
+use tabled::settings::{Alignment, Color, Style};
+
+use crate::chart::print_chart;
+use crate::commands::{display_tasks, ReadableOptions, SheetFilter};
+use crate::config::Config;
+use crate::database::get_all_entries;
+use crate::range::DateRange;
+use crate::style::{style_string, Styles};
+use crate::utils::{duration_by_day, format_hours};
+use crate::Entry;
+use crate::State;
+
+pub fn display_week(
+    week: Option<&String>,
+    offset: &i64,
+    matrix: &bool,
+    sheet: Option<&String>,
+    options: &ReadableOptions,
+    config: &Config,
+    state: &State,
+) -> Result<()> {
+    // Any day of the week can be used to choose it, e.g. "2024-W12" or "2024-03-20"
+    let day = match week {
+        Some(week) => DateRange::parse(week, config.week_start)?.start.date_naive(),
+        None => Local::now().date_naive(),
+    };
+
+    let range = DateRange::week(day + Duration::weeks(*offset), config.week_start)?;
+
+    match matrix {
+        true => print_matrix(&range, options, state),
+        false => display_tasks(
+            &false,
+            SheetFilter::Sheet(sheet),
+            Some(range.start),
+            Some(range.end),
+            &false,
+            options,
+            state,
+        ),
+    }
+}
+
+// The hours of each sheet in the days of the week, with the totals
+fn print_matrix(range: &DateRange, options: &ReadableOptions, state: &State) -> Result<()> {
+    let mut entries = get_all_entries(&state.database)?;
+    entries.retain(|e| e.start >= range.start && e.start <= range.end);
+
+    let days: Vec<_> = range
+        .start
+        .date_naive()
+        .iter_days()
+        .take_while(|d| *d <= range.end.date_naive())
+        .collect();
+
+    println!(
+        "{} {} - {}",
+        style_string("Hours per day:", Styles::Title),
+        range.start.format("%a %b %d, %Y"),
+        range.end.format("%a %b %d, %Y")
+    );
+
+    let mut builder = Builder::new();
+
+    let mut headings = vec!["Sheet".to_string()];
+    headings.extend(days.iter().map(|d| d.format("%a %d").to_string()));
+    headings.push("Total".to_string());
+    builder.push_record(headings);
+
+    let mut sheets: BTreeMap<&str, Vec<Entry>> = BTreeMap::new();
+    for entry in &entries {
+        sheets.entry(&entry.sheet).or_default().push(entry.clone());
+    }
+
+    for (sheet, sheet_entries) in &sheets {
+        builder.push_record(matrix_row(sheet, &days, sheet_entries));
+    }
+
+    builder.push_record(matrix_row("Total", &days, &entries));
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.modify(Columns::new(1..), Alignment::right());
+    table.with(Colorization::exact([Color::BOLD], Rows::first()));
+    table.with(Colorization::exact([Color::BOLD], Rows::last()));
+
+    println!("{}", table);
+
+    if let Some(by) = options.chart {
+        print_chart(by, &entries);
+    }
+
+    Ok(())
+}
+
+fn matrix_row(name: &str, days: &[NaiveDate], entries: &[Entry]) -> Vec<String> {
+    let by_day = duration_by_day(entries);
+
+    let mut row = vec![name.to_string()];
+    row.extend(days.iter().map(|d| match by_day.get(d) {
+        Some(duration) => format_hours(duration),
+        None => "-".to_string(),
+    }));
+    row.push(format_hours(&by_day.values().sum()));
+
+    row
+}
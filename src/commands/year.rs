@@ -0,0 +1,248 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use tabled::builder::Builder;
+use tabled::settings::object::{Columns, Rows};
+use tabled::settings::themes::Colorization;
+use tabled::settings::{Alignment, Color, Style};
+
+use crate::chart::use_unicode;
+use crate::config::Config;
+use crate::database::get_all_entries;
+use crate::style::{style_string, Styles};
+use crate::utils::{day_end, duration_by_day, format_hours, get_month_boundaries};
+use crate::Entry;
+use crate::State;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Used to color the heatmap when there is no daily target
+const DEFAULT_TARGET_HOURS: i64 = 8;
+
+// The heatmap levels are told apart by their glyphs too, since the colors may be disabled
+const HEATMAP_LEVELS: [&str; 6] = ["·", "░", "▒", "▓", "█", "█"];
+const ASCII_HEATMAP_LEVELS: [&str; 6] = [".", "-", "+", "*", "#", "!"];
+
+pub fn display_year(
+    year: Option<i32>,
+    sheet: Option<&String>,
+    config: &Config,
+    state: &State,
+) -> Result<()> {
+    let year = year.unwrap_or(Local::now().year());
+
+    // Archived sheets are included, since this is a look at the past
+    let mut entries = get_all_entries(&state.database)?;
+    entries.retain(|e| e.start.year() == year);
+
+    if let Some(sheet) = sheet {
+        entries.retain(|e| &e.sheet == sheet);
+    }
+
+    println!("{} {}", style_string("Year:", Styles::Title), year);
+
+    print_months_table(year, &entries)?;
+
+    println!();
+    print_heatmap(year, &entries, config);
+
+    Ok(())
+}
+
+// The hours of each sheet in every month
+fn print_months_table(year: i32, entries: &[Entry]) -> Result<()> {
+    let mut months = Vec::new();
+
+    for month in 1..=12 {
+        let (start, end) = get_month_boundaries(&format!("{}-{:02}", year, month))?;
+        months.push((start, day_end(end)));
+    }
+
+    let sheets: BTreeSet<&str> = entries.iter().map(|e| e.sheet.as_str()).collect();
+
+    let mut builder = Builder::new();
+
+    let mut headings = vec!["Sheet"];
+    headings.extend(MONTHS);
+    headings.push("Total");
+    builder.push_record(headings);
+
+    let row = |name: &str, entries: Vec<&Entry>| {
+        let mut row = vec![name.to_string()];
+
+        for (start, end) in &months {
+            let total: Duration = entries
+                .iter()
+                .filter(|e| e.start >= *start && e.start <= *end)
+                .map(|e| e.get_duration())
+                .sum();
+
+            row.push(match total.is_zero() {
+                true => "-".to_string(),
+                false => format_hours(&total),
+            });
+        }
+
+        row.push(format_hours(&entries.iter().map(|e| e.get_duration()).sum()));
+        row
+    };
+
+    for sheet in sheets {
+        builder.push_record(row(sheet, entries.iter().filter(|e| e.sheet == sheet).collect()));
+    }
+
+    builder.push_record(row("Total", entries.iter().collect()));
+
+    let mut table = builder.build();
+    table.with(Style::empty());
+    table.modify(Columns::new(1..), Alignment::right());
+    table.with(Colorization::exact([Color::BOLD], Rows::first()));
+    table.with(Colorization::exact([Color::BOLD], Rows::last()));
+
+    println!("{}", table);
+
+    Ok(())
+}
+
+// A calendar of the year with a column for each week, colored by the hours of each day
+fn print_heatmap(year: i32, entries: &[Entry], config: &Config) {
+    let by_day = duration_by_day(entries);
+    let target = config
+        .daily_target
+        .unwrap_or(Duration::hours(DEFAULT_TARGET_HOURS));
+
+    let first_day = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+    let last_day = NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
+    let first_week = first_day.week(config.week_start).first_day();
+
+    let weeks: Vec<NaiveDate> = first_week
+        .iter_weeks()
+        .take_while(|w| *w <= last_day)
+        .collect();
+
+    // The month names, above the week where each month starts
+    let mut labels = String::new();
+    for (i, week) in weeks.iter().enumerate() {
+        if labels.chars().count() > i {
+            continue;
+        }
+
+        let month_start = (0..7)
+            .map(|d| *week + Duration::days(d))
+            .find(|d| d.day() == 1 && d.year() == year);
+
+        match month_start {
+            Some(d) => labels.push_str(MONTHS[d.month0() as usize]),
+            None => labels.push(' '),
+        }
+    }
+    println!("    {}", labels);
+
+    let unicode = use_unicode();
+
+    for weekday in 0..7 {
+        let name = (first_week + Duration::days(weekday)).format("%a").to_string();
+        let mut line = format!("{} ", name);
+
+        for week in &weeks {
+            let day = *week + Duration::days(weekday);
+
+            if day.year() != year {
+                line.push(' ');
+                continue;
+            }
+
+            let hours = by_day.get(&day).copied().unwrap_or(Duration::zero());
+            line.push_str(&heatmap_cell(heatmap_level(hours, target), unicode).to_string());
+        }
+
+        println!("{}", line);
+    }
+
+    let legend: Vec<String> = (0..6)
+        .map(|level| heatmap_cell(level, unicode).to_string())
+        .collect();
+    let over_target = match unicode {
+        true => "in red",
+        false => "as !",
+    };
+    println!("    Less {} More (over target {})", legend.join(""), over_target);
+
+    print_busiest_week(&weeks, &by_day);
+}
+
+fn print_busiest_week(weeks: &[NaiveDate], by_day: &BTreeMap<NaiveDate, Duration>) {
+    let busiest = weeks
+        .iter()
+        .map(|week| {
+            let total: Duration = (0..7)
+                .filter_map(|d| by_day.get(&(*week + Duration::days(d))))
+                .copied()
+                .sum();
+            (week, total)
+        })
+        .max_by_key(|(_, total)| *total);
+
+    if let Some((week, total)) = busiest.filter(|(_, total)| !total.is_zero()) {
+        println!(
+            "{} week of {} ({} hours)",
+            style_string("Busiest week:", Styles::Title),
+            week.format("%b %d"),
+            format_hours(&total)
+        );
+    }
+}
+
+// The level of a day, from 0 (nothing tracked) to 5 (well over the daily target)
+fn heatmap_level(hours: Duration, target: Duration) -> usize {
+    let ratio = hours.num_seconds() as f64 / target.num_seconds().max(1) as f64;
+
+    match ratio {
+        r if r <= 0.0 => 0,
+        r if r < 0.25 => 1,
+        r if r < 0.5 => 2,
+        r if r < 1.0 => 3,
+        r if r <= 1.25 => 4,
+        _ => 5,
+    }
+}
+
+fn heatmap_cell(level: usize, unicode: bool) -> colored::ColoredString {
+    let glyph = match unicode {
+        true => HEATMAP_LEVELS[level],
+        false => ASCII_HEATMAP_LEVELS[level],
+    };
+
+    match level {
+        0 => glyph.bright_black(),
+        1 => glyph.truecolor(14, 68, 41),
+        2 => glyph.truecolor(0, 109, 50),
+        3 => glyph.truecolor(38, 166, 65),
+        4 => glyph.truecolor(57, 211, 83),
+        _ => glyph.red(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_level() {
+        let target = Duration::hours(8);
+
+        assert_eq!(heatmap_level(Duration::zero(), target), 0);
+        assert_eq!(heatmap_level(Duration::hours(1), target), 1);
+        assert_eq!(heatmap_level(Duration::hours(3), target), 2);
+        assert_eq!(heatmap_level(Duration::hours(6), target), 3);
+        assert_eq!(heatmap_level(Duration::hours(8), target), 4);
+        assert_eq!(heatmap_level(Duration::hours(11), target), 5);
+
+        // Without colors, each level needs its own glyph
+        let glyphs: BTreeSet<&str> = ASCII_HEATMAP_LEVELS.into_iter().collect();
+        assert_eq!(glyphs.len(), ASCII_HEATMAP_LEVELS.len());
+    }
+}
@@ -1,11 +1,41 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, NaiveTime, Weekday};
+use serde::Deserialize;
+use std::fs;
 
 use directories::ProjectDirs;
 
+use crate::hooks::Hooks;
+use crate::utils::parse_duration;
+
 #[derive(Debug)]
 pub struct Config {
     pub database_file: String,
     pub default_sheet: String,
+    pub daily_target: Option<Duration>,
+    pub status_template: String,
+    pub status_stopped_template: String,
+    pub auto_snapshots: bool,
+    pub snapshot_retention: usize,
+    pub working_hours: (NaiveTime, NaiveTime),
+    pub working_days: Vec<Weekday>,
+    pub week_start: Weekday,
+    pub hooks: Hooks,
+}
+
+// The optional user configuration, stored as config.json in the config directory
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    daily_target: Option<String>,
+    status_template: Option<String>,
+    status_stopped_template: Option<String>,
+    auto_snapshots: Option<bool>,
+    snapshot_retention: Option<usize>,
+    working_hours: Option<String>,
+    working_days: Option<Vec<String>>,
+    week_start: Option<String>,
+    hooks: Hooks,
 }
 
 impl Config {
@@ -13,6 +43,34 @@ impl Config {
         let proj_dirs = ProjectDirs::from("com", "andreadev-it", "timetrack")
             .ok_or(anyhow!("Cannot get project directories for this OS."))?;
 
+        let file = read_config_file(&proj_dirs)?;
+
+        let daily_target = file
+            .daily_target
+            .as_ref()
+            .map(|t| parse_duration(t))
+            .transpose()
+            .context("Invalid value for \"daily_target\" in the configuration file.")?;
+
+        let working_hours = file.working_hours.as_deref().unwrap_or("09:00-18:00");
+        let working_hours = parse_working_hours(working_hours).context(
+            "Invalid value for \"working_hours\" in the configuration file.",
+        )?;
+
+        let working_days = match &file.working_days {
+            Some(days) => parse_working_days(days).context(
+                "Invalid value for \"working_days\" in the configuration file.",
+            )?,
+            None => vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+        };
+
+        let week_start = file
+            .week_start
+            .as_deref()
+            .unwrap_or("monday")
+            .parse::<Weekday>()
+            .map_err(|_| anyhow!("Invalid value for \"week_start\" in the configuration file."))?;
+
         // Get the data file path
         let data_dir = proj_dirs.data_local_dir();
         let mut db_file = data_dir.to_path_buf();
@@ -22,9 +80,61 @@ impl Config {
             return Ok(Config {
                 database_file: db_file_str.to_string(),
                 default_sheet: "default".to_string(),
+                daily_target,
+                status_template: file
+                    .status_template
+                    .unwrap_or("{sheet}: {note} {elapsed}".to_string()),
+                status_stopped_template: file
+                    .status_stopped_template
+                    .unwrap_or("{sheet}: stopped".to_string()),
+                auto_snapshots: file.auto_snapshots.unwrap_or(false),
+                snapshot_retention: file.snapshot_retention.unwrap_or(10),
+                working_hours,
+                working_days,
+                week_start,
+                hooks: file.hooks,
             });
         }
 
         Err(anyhow!("Seems like the path contains invalid unicode. Please forward this to the developer. The path was: {:?}", db_file))
     }
 }
+
+// Working hours are written as "09:00-18:00"
+fn parse_working_hours(s: &str) -> Result<(NaiveTime, NaiveTime)> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or(anyhow!("Expected a range like \"09:00-18:00\", found: {}", s))?;
+
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M")?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M")?;
+
+    if start >= end {
+        return Err(anyhow!("The working hours must end after they start: {}", s));
+    }
+
+    Ok((start, end))
+}
+
+// Working days are written as ["monday", "tuesday"] or ["mon", "tue"]
+fn parse_working_days(days: &[String]) -> Result<Vec<Weekday>> {
+    days.iter()
+        .map(|day| day.parse::<Weekday>().map_err(|_| anyhow!("Unknown day: {}", day)))
+        .collect()
+}
+
+fn read_config_file(proj_dirs: &ProjectDirs) -> Result<ConfigFile> {
+    let mut config_file = proj_dirs.config_dir().to_path_buf();
+    config_file.push("config.json");
+
+    // The configuration file is optional
+    let content = match fs::read_to_string(&config_file) {
+        Ok(content) => content,
+        Err(_) => return Ok(ConfigFile::default()),
+    };
+
+    serde_json::from_str(&content).context(format!(
+        "Cannot parse the configuration file. The path was: {:?}",
+        config_file
+    ))
+}
@@ -1,14 +1,25 @@
 use anyhow::{anyhow, Context, Result};
-use rusqlite::{named_params, Connection};
+use chrono::{DateTime, Local};
+use rusqlite::types::Type;
+use rusqlite::{named_params, params, Connection, ErrorCode, Row};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 use crate::config::Config;
 use crate::entry::Entry;
+use crate::template::Template;
 use crate::utils::str_to_datetime;
 
+// How long to wait for other processes that are writing to the database
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub fn connect_to_db(config: &Config) -> Result<Connection> {
     if let Ok(conn) = Connection::open(&config.database_file) {
+        // WAL mode lets readers work while another process is writing
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
         return Ok(conn);
     }
 
@@ -40,15 +51,199 @@ pub fn create_tables(db: &Connection) -> Result<()> {
         start TIMESTAMP NOT NULL,
         end TIMESTAMP,
         sheet VARCHAR(255) NOT NULL
-    )
+    );
+
+    CREATE INDEX IF NOT EXISTS entries_sheet_end ON entries (sheet, end);
     ";
 
-    db.execute(query, ())
+    db.execute_batch(query)
         .context("Tables could not be created in the database")?;
 
+    run_migrations(db)?;
+
+    Ok(())
+}
+
+// Every migration is applied only once, in order. The number of applied
+// migrations is stored in the "user_version" pragma of the database.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE entries ADD COLUMN tags TEXT NOT NULL DEFAULT ''",
+    "CREATE TABLE meta (key VARCHAR(255) NOT NULL PRIMARY KEY, value TEXT NOT NULL)",
+    // Only the latest running entry of each sheet keeps running, the others are stopped
+    // when the following entry started
+    "UPDATE entries SET end = (
+        SELECT MIN(next.start) FROM entries AS next
+        WHERE next.sheet = entries.sheet
+        AND (next.start > entries.start OR (next.start = entries.start AND next.id > entries.id))
+    )
+    WHERE end IS NULL AND EXISTS (
+        SELECT 1 FROM entries AS later
+        WHERE later.sheet = entries.sheet AND later.end IS NULL
+        AND (later.start > entries.start OR (later.start = entries.start AND later.id > entries.id))
+    );
+    CREATE UNIQUE INDEX entries_one_running ON entries (sheet) WHERE end IS NULL;",
+    "CREATE TABLE sheets (name VARCHAR(255) NOT NULL PRIMARY KEY, archived_at TIMESTAMP)",
+    "ALTER TABLE entries ADD COLUMN deleted_at TIMESTAMP;
+    DROP INDEX entries_one_running;
+    CREATE UNIQUE INDEX entries_one_running ON entries (sheet) WHERE end IS NULL AND deleted_at IS NULL;",
+    // Full-text index of the notes, kept in sync with the entries by the triggers
+    "CREATE VIRTUAL TABLE entries_fts USING fts5 (note, content = 'entries', content_rowid = 'id');
+    INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');
+    CREATE TRIGGER entries_fts_insert AFTER INSERT ON entries BEGIN
+        INSERT INTO entries_fts (rowid, note) VALUES (new.id, new.note);
+    END;
+    CREATE TRIGGER entries_fts_delete AFTER DELETE ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, note) VALUES ('delete', old.id, old.note);
+    END;
+    CREATE TRIGGER entries_fts_update AFTER UPDATE OF note ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, note) VALUES ('delete', old.id, old.note);
+        INSERT INTO entries_fts (rowid, note) VALUES (new.id, new.note);
+    END;",
+    // The duration is in seconds
+    "CREATE TABLE templates (
+        name VARCHAR(255) NOT NULL PRIMARY KEY,
+        note VARCHAR(255) NOT NULL,
+        sheet VARCHAR(255),
+        tags TEXT NOT NULL DEFAULT '',
+        duration INTEGER
+    )",
+];
+
+// Each migration is saved together with the new version, so a failed one leaves the
+// database as it was. The version is read again after taking the lock, in case another
+// process has migrated the database in the meantime.
+fn run_migrations(db: &Connection) -> Result<()> {
+    // The lock is only taken when there is something to do, so the commands that only
+    // read don't wait for the ones that write
+    let version: usize = db.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version >= MIGRATIONS.len() {
+        return Ok(());
+    }
+
+    loop {
+        begin_transaction(db, true)?;
+
+        let version: usize = db.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        let Some(migration) = MIGRATIONS.get(version) else {
+            commit_transaction(db)?;
+            return Ok(());
+        };
+
+        let res = db
+            .execute_batch(migration)
+            .and_then(|_| db.pragma_update(None, "user_version", version + 1));
+
+        match res {
+            Ok(_) => commit_transaction(db)?,
+            Err(e) => {
+                rollback_transaction(db)?;
+                return Err(e)
+                    .context(format!("Could not migrate the database to version {}", version + 1));
+            }
+        };
+    }
+}
+
+// Every command runs inside a transaction. They are handled manually, since the
+// connection is owned by the state, which is also modified while the transaction
+// is open. If the program exits before committing, the transaction is rolled back.
+pub fn begin_transaction(db: &Connection, writes: bool) -> Result<()> {
+    match writes {
+        true => db.execute_batch("BEGIN IMMEDIATE")?,
+        false => db.execute_batch("BEGIN DEFERRED")?,
+    };
+
+    Ok(())
+}
+
+// A failed commit, e.g. while another process holds the lock, leaves the transaction
+// open, which would hold back the next request of `t serve` or `t rpc`
+pub fn commit_transaction(db: &Connection) -> Result<()> {
+    if let Err(err) = db.execute_batch("COMMIT") {
+        if !db.is_autocommit() {
+            let _ = db.execute_batch("ROLLBACK");
+        }
+
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+pub fn rollback_transaction(db: &Connection) -> Result<()> {
+    db.execute_batch("ROLLBACK")?;
+
     Ok(())
 }
 
+// How SQLite reports a second running entry in a sheet
+const ONE_RUNNING_VIOLATION: &str = "UNIQUE constraint failed: entries.sheet";
+
+// Explains the database errors that the user can do something about: the ones
+// caused by other processes changing the database at the same time, and the
+// ones caused by broken data.
+pub fn explain_error(err: &anyhow::Error) -> Option<&'static str> {
+    let err = err
+        .chain()
+        .find_map(|e| e.downcast_ref::<rusqlite::Error>())?;
+
+    match err {
+        rusqlite::Error::SqliteFailure(e, message) => match e.code {
+            ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Some(
+                "The database is being changed by another timetrack command. Please try again.",
+            ),
+            // Only the entries_one_running index makes the sheet of the entries unique
+            ErrorCode::ConstraintViolation
+                if message.as_deref() == Some(ONE_RUNNING_VIOLATION) =>
+            {
+                Some(
+                    "There can only be one running task in each sheet. If it wasn't started from another terminal, run `t doctor` to repair the database.",
+                )
+            }
+            _ => None,
+        },
+        rusqlite::Error::FromSqlConversionFailure(..) => {
+            Some("Some entries in the database are broken. Run `t doctor` to find and repair them.")
+        }
+        _ => None,
+    }
+}
+
+// Parses a timestamp column, reporting the broken values as row errors
+fn datetime_from_row(row: &Row, idx: usize) -> rusqlite::Result<Option<DateTime<Local>>> {
+    row.get::<usize, Option<String>>(idx)?
+        .map(|t| str_to_datetime(&t))
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, e.into()))
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<Entry> {
+    let end = datetime_from_row(row, 3)?;
+
+    let start = datetime_from_row(row, 2)?.ok_or(rusqlite::Error::InvalidColumnType(
+        2,
+        "start".to_string(),
+        Type::Null,
+    ))?;
+
+    let tags = row
+        .get::<usize, String>(5)?
+        .split(',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect();
+
+    Ok(Entry {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        start,
+        end,
+        sheet: row.get(4)?,
+        tags,
+    })
+}
+
 pub fn write_entry(entry: &Entry, db: &Connection) -> Result<()> {
     match entry.id {
         Some(_) => update_entry(entry, db)?,
@@ -64,7 +259,8 @@ pub fn update_entry(entry: &Entry, db: &Connection) -> Result<()> {
         note = :note,
         start = :start,
         end = :end,
-        sheet = :sheet
+        sheet = :sheet,
+        tags = :tags
     WHERE
         id = :id
     ";
@@ -75,6 +271,7 @@ pub fn update_entry(entry: &Entry, db: &Connection) -> Result<()> {
         ":start": entry.start,
         ":end": entry.end,
         ":sheet": entry.sheet,
+        ":tags": entry.tags.join(","),
         ":id": entry.id
     })?;
 
@@ -83,8 +280,8 @@ pub fn update_entry(entry: &Entry, db: &Connection) -> Result<()> {
 
 pub fn create_entry(entry: &Entry, db: &Connection) -> Result<()> {
     let query = "
-    INSERT INTO entries (note, start, end, sheet) VALUES (
-        :note, :start, :end, :sheet
+    INSERT INTO entries (note, start, end, sheet, tags) VALUES (
+        :note, :start, :end, :sheet, :tags
     )
     ";
 
@@ -93,7 +290,8 @@ pub fn create_entry(entry: &Entry, db: &Connection) -> Result<()> {
         ":note": entry.name,
         ":start": entry.start,
         ":end": entry.end,
-        ":sheet": entry.sheet
+        ":sheet": entry.sheet,
+        ":tags": entry.tags.join(",")
     })?;
 
     Ok(())
@@ -101,56 +299,25 @@ pub fn create_entry(entry: &Entry, db: &Connection) -> Result<()> {
 
 pub fn running_entries(db: &Connection) -> Result<Vec<Entry>> {
     let query = "
-    SELECT id, note, start, end, sheet FROM entries WHERE end IS NULL;
+    SELECT id, note, start, end, sheet, tags FROM entries WHERE end IS NULL AND deleted_at IS NULL;
     ";
 
     let mut stmt = db.prepare(query)?;
-    let entries = stmt.query_map([], |row| {
-        let end = row
-            .get::<usize, Option<String>>(3)?
-            .map(|t| str_to_datetime(&t).unwrap());
-
-        let start = str_to_datetime(&row.get::<usize, String>(2)?).unwrap();
-
-        Ok(Entry {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            start,
-            end,
-            sheet: row.get(4)?,
-        })
-    })?;
-
-    let entries_vec: Vec<Result<Entry, _>> = entries.collect();
+    let entries = stmt.query_map([], entry_from_row)?;
 
-    if entries_vec.iter().any(|e| e.is_err()) {
-        return Err(anyhow!("Error while parsing the running entries"));
-    }
-
-    Ok(entries_vec.into_iter().map(|e| e.unwrap()).collect())
+    entries
+        .collect::<Result<Vec<Entry>, _>>()
+        .context("Error while parsing the running entries")
 }
 
 pub fn running_entry(db: &Connection, sheet: &str) -> Result<Option<Entry>> {
     let query = "
-    SELECT id, note, start, end, sheet FROM entries WHERE end IS NULL AND sheet = ?;
+    SELECT id, note, start, end, sheet, tags FROM entries
+    WHERE end IS NULL AND sheet = ? AND deleted_at IS NULL;
     ";
 
     let mut stmt = db.prepare(query)?;
-    let mut entries = stmt.query_map([sheet], |row| {
-        let end = row
-            .get::<usize, Option<String>>(3)?
-            .map(|t| str_to_datetime(&t).unwrap());
-
-        let start = str_to_datetime(&row.get::<usize, String>(2)?).unwrap();
-
-        Ok(Entry {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            start,
-            end,
-            sheet: row.get(4)?,
-        })
-    })?;
+    let mut entries = stmt.query_map([sheet], entry_from_row)?;
 
     let running_entry = entries.next();
 
@@ -161,25 +328,11 @@ pub fn running_entry(db: &Connection, sheet: &str) -> Result<Option<Entry>> {
 
 pub fn get_all_entries(db: &Connection) -> Result<Vec<Entry>> {
     let query = "
-    SELECT id, note, start, end, sheet FROM entries;
+    SELECT id, note, start, end, sheet, tags FROM entries WHERE deleted_at IS NULL;
     ";
 
     let mut stmt = db.prepare(query)?;
-    let entries = stmt.query_map([], |row| {
-        let end = row
-            .get::<usize, Option<String>>(3)?
-            .map(|t| str_to_datetime(&t).unwrap());
-
-        let start = str_to_datetime(&row.get::<usize, String>(2)?).unwrap();
-
-        Ok(Entry {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            start,
-            end,
-            sheet: row.get(4)?,
-        })
-    })?;
+    let entries = stmt.query_map([], entry_from_row)?;
 
     let mut entries_vec = Vec::new();
 
@@ -190,27 +343,29 @@ pub fn get_all_entries(db: &Connection) -> Result<Vec<Entry>> {
     Ok(entries_vec)
 }
 
+// The timestamps are stored in UTC with the same format, so they can be compared as text
+// The entries with some time after the given one, including those started before it
+pub fn get_entries_since(since: DateTime<Local>, db: &Connection) -> Result<Vec<Entry>> {
+    let query = "
+    SELECT id, note, start, end, sheet, tags FROM entries
+    WHERE (end IS NULL OR end > ?1) AND deleted_at IS NULL;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    let entries = stmt.query_map([since], entry_from_row)?;
+
+    entries
+        .collect::<Result<Vec<Entry>, _>>()
+        .context("Error while parsing the entries")
+}
+
 pub fn get_entry_by_id(id: &usize, db: &Connection) -> Result<Option<Entry>> {
     let query = "
-    SELECT id, note, start, end, sheet FROM entries WHERE id = ?;
+    SELECT id, note, start, end, sheet, tags FROM entries WHERE id = ? AND deleted_at IS NULL;
     ";
 
     let mut stmt = db.prepare(query)?;
-    let mut entries = stmt.query_map([id], |row| {
-        let end = row
-            .get::<usize, Option<String>>(3)?
-            .map(|t| str_to_datetime(&t).unwrap());
-
-        let start = str_to_datetime(&row.get::<usize, String>(2)?).unwrap();
-
-        Ok(Entry {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            start,
-            end,
-            sheet: row.get(4)?,
-        })
-    })?;
+    let mut entries = stmt.query_map([id], entry_from_row)?;
 
     let entry = entries.next();
 
@@ -219,7 +374,75 @@ pub fn get_entry_by_id(id: &usize, db: &Connection) -> Result<Option<Entry>> {
         .context("Error while parsing the current entry")
 }
 
+// Removed entries are moved to the trash, from where they can be restored
 pub fn remove_entry_by_id(id: &usize, db: &Connection) -> Result<()> {
+    let query = "
+    UPDATE entries SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute(params![Local::now(), id])?;
+
+    Ok(())
+}
+
+pub fn get_deleted_entries(db: &Connection) -> Result<Vec<(Entry, DateTime<Local>)>> {
+    let query = "
+    SELECT id, note, start, end, sheet, tags, deleted_at FROM entries WHERE deleted_at IS NOT NULL;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    let entries = stmt.query_map([], |row| {
+        let deleted_at = datetime_from_row(row, 6)?.ok_or(rusqlite::Error::InvalidColumnType(
+            6,
+            "deleted_at".to_string(),
+            Type::Null,
+        ))?;
+
+        Ok((entry_from_row(row)?, deleted_at))
+    })?;
+
+    // The doctor moves the entries with unreadable times to the trash, where they can
+    // only be purged
+    let mut deleted = Vec::new();
+
+    for entry in entries {
+        match entry {
+            Ok(entry) => deleted.push(entry),
+            Err(rusqlite::Error::FromSqlConversionFailure(..)) => {}
+            Err(e) => return Err(e).context("Error while parsing the deleted entries"),
+        }
+    }
+
+    Ok(deleted)
+}
+
+// The IDs of the entries in the trash and when they were deleted, including the
+// unreadable ones
+pub fn get_deleted_ids(db: &Connection) -> Result<Vec<(usize, DateTime<Local>)>> {
+    let query = "
+    SELECT id, deleted_at FROM entries WHERE deleted_at IS NOT NULL;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    let ids = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    ids.collect::<Result<Vec<_>, _>>().context("Error while reading the deleted entries")
+}
+
+pub fn restore_entry_by_id(id: &usize, db: &Connection) -> Result<()> {
+    let query = "
+    UPDATE entries SET deleted_at = NULL WHERE id = ?;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute([id])?;
+
+    Ok(())
+}
+
+// Removes an entry for good, whether it is in the trash or not
+pub fn purge_entry_by_id(id: &usize, db: &Connection) -> Result<()> {
     let query = "
     DELETE FROM entries WHERE id = ?;
     ";
@@ -230,6 +453,71 @@ pub fn remove_entry_by_id(id: &usize, db: &Connection) -> Result<()> {
     Ok(())
 }
 
+// The entries whose note matches a full-text query, from the best match to the worst
+pub fn search_entries(query: &str, sheet: Option<&str>, db: &Connection) -> Result<Vec<Entry>> {
+    let sql = "
+    SELECT e.id, e.note, e.start, e.end, e.sheet, e.tags
+    FROM entries_fts f JOIN entries e ON e.id = f.rowid
+    WHERE entries_fts MATCH :query
+        AND e.deleted_at IS NULL
+        AND (:sheet IS NULL OR e.sheet = :sheet)
+    ORDER BY f.rank, e.start DESC;
+    ";
+
+    let fts = fts_query(query);
+
+    // An empty query is an error for FTS5
+    if fts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = db.prepare(sql)?;
+    let entries = stmt.query_map(
+        named_params! { ":query": fts, ":sheet": sheet },
+        entry_from_row,
+    )?;
+
+    entries
+        .collect::<Result<Vec<Entry>, _>>()
+        .context(format!("Invalid search query: {}", query))
+}
+
+// Every word is quoted, so that the punctuation and the FTS5 operators are searched
+// like any other text. Only the "phrases" and the * at the end of a prefix are kept.
+fn fts_query(query: &str) -> String {
+    let mut terms = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        // An unterminated phrase goes on until the end of the query
+        if c == '"' {
+            let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+
+            if !phrase.trim().is_empty() {
+                terms.push(format!("\"{}\"", phrase));
+            }
+            continue;
+        }
+
+        let mut word = c.to_string();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"') {
+            word.push(c);
+        }
+
+        match word.strip_suffix('*') {
+            Some(prefix) if !prefix.is_empty() => terms.push(format!("\"{}\"*", prefix)),
+            Some(_) => {}
+            None => terms.push(format!("\"{}\"", word)),
+        };
+    }
+
+    terms.join(" ")
+}
+
 pub fn get_sheet_entries(sheet: &str, db: &Connection) -> Result<Vec<Entry>> {
     let entries = get_all_entries(db)?;
 
@@ -242,7 +530,7 @@ pub fn get_sheet_entries(sheet: &str, db: &Connection) -> Result<Vec<Entry>> {
 
 pub fn get_all_sheets(db: &Connection) -> Result<Vec<String>> {
     let query = "
-    SELECT DISTINCT sheet FROM entries;
+    SELECT DISTINCT sheet FROM entries WHERE deleted_at IS NULL;
     ";
 
     let mut stmt = db.prepare(query)?;
@@ -257,6 +545,18 @@ pub fn get_all_sheets(db: &Connection) -> Result<Vec<String>> {
     Ok(sheets)
 }
 
+// Whether a sheet has any entries, also in the trash, or is archived
+pub fn sheet_exists(name: &str, db: &Connection) -> Result<bool> {
+    let query = "
+    SELECT EXISTS (SELECT 1 FROM entries WHERE sheet = :name)
+        OR EXISTS (SELECT 1 FROM sheets WHERE name = :name);
+    ";
+
+    let exists = db.query_row(query, named_params! { ":name": name }, |row| row.get(0))?;
+
+    Ok(exists)
+}
+
 pub fn update_sheet_name(old_name: &str, new_name: &str, db: &Connection) -> Result<()> {
     let query = "
     UPDATE entries SET sheet = ? WHERE sheet = ?
@@ -264,16 +564,349 @@ pub fn update_sheet_name(old_name: &str, new_name: &str, db: &Connection) -> Res
     let mut stmt = db.prepare(query)?;
     stmt.execute([new_name, old_name])?;
 
+    // The archived state follows the sheet
+    let query = "
+    UPDATE sheets SET name = ? WHERE name = ?
+    ";
+    let mut stmt = db.prepare(query)?;
+    stmt.execute([new_name, old_name])?;
+
     Ok(())
 }
 
+// The archived state is kept, in case the entries are restored
 pub fn remove_entries_by_sheet(sheet: &str, db: &Connection) -> Result<()> {
     let query = "
-    DELETE FROM entries WHERE sheet = ?;
+    UPDATE entries SET deleted_at = ? WHERE sheet = ? AND deleted_at IS NULL;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute(params![Local::now(), sheet])?;
+
+    Ok(())
+}
+
+pub fn get_archived_sheets(db: &Connection) -> Result<Vec<String>> {
+    let query = "
+    SELECT name FROM sheets WHERE archived_at IS NOT NULL;
     ";
 
     let mut stmt = db.prepare(query)?;
-    stmt.execute([sheet])?;
+    let rows = stmt.query_map([], |row| row.get::<usize, String>(0))?;
+
+    let mut sheets = Vec::new();
+
+    for sheet in rows {
+        sheets.push(sheet?);
+    }
+
+    Ok(sheets)
+}
+
+pub fn is_sheet_archived(sheet: &str, db: &Connection) -> Result<bool> {
+    let query = "
+    SELECT EXISTS (SELECT 1 FROM sheets WHERE name = ? AND archived_at IS NOT NULL);
+    ";
+
+    Ok(db.query_row(query, [sheet], |row| row.get(0))?)
+}
+
+pub fn set_sheet_archived(sheet: &str, archived: bool, db: &Connection) -> Result<()> {
+    let archived_at = match archived {
+        true => Some(Local::now()),
+        false => None,
+    };
+
+    let query = "
+    INSERT INTO sheets (name, archived_at) VALUES (:name, :archived_at)
+    ON CONFLICT (name) DO UPDATE SET archived_at = excluded.archived_at;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute(named_params! {
+        ":name": sheet,
+        ":archived_at": archived_at
+    })?;
 
     Ok(())
 }
+
+fn template_from_row(row: &Row) -> rusqlite::Result<Template> {
+    let tags = row
+        .get::<usize, String>(3)?
+        .split(',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect();
+
+    Ok(Template {
+        name: row.get(0)?,
+        note: row.get(1)?,
+        sheet: row.get(2)?,
+        tags,
+        duration: row.get::<usize, Option<i64>>(4)?.map(chrono::Duration::seconds),
+    })
+}
+
+pub fn get_all_templates(db: &Connection) -> Result<Vec<Template>> {
+    let query = "
+    SELECT name, note, sheet, tags, duration FROM templates ORDER BY name;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    let templates = stmt.query_map([], template_from_row)?;
+
+    templates
+        .collect::<Result<Vec<Template>, _>>()
+        .context("Error while reading the templates")
+}
+
+pub fn get_template(name: &str, db: &Connection) -> Result<Option<Template>> {
+    let query = "
+    SELECT name, note, sheet, tags, duration FROM templates WHERE name = ?;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    let mut templates = stmt.query_map([name], template_from_row)?;
+
+    Ok(templates.next().transpose()?)
+}
+
+pub fn write_template(template: &Template, db: &Connection) -> Result<()> {
+    let query = "
+    INSERT INTO templates (name, note, sheet, tags, duration) VALUES (
+        :name, :note, :sheet, :tags, :duration
+    )
+    ON CONFLICT (name) DO UPDATE SET
+        note = excluded.note,
+        sheet = excluded.sheet,
+        tags = excluded.tags,
+        duration = excluded.duration;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute(named_params! {
+        ":name": template.name,
+        ":note": template.note,
+        ":sheet": template.sheet,
+        ":tags": template.tags.join(","),
+        ":duration": template.duration.map(|d| d.num_seconds())
+    })?;
+
+    Ok(())
+}
+
+pub fn delete_template(name: &str, db: &Connection) -> Result<()> {
+    let query = "
+    DELETE FROM templates WHERE name = ?;
+    ";
+
+    db.execute(query, [name])?;
+
+    Ok(())
+}
+
+pub fn read_sheet_state(db: &Connection) -> Result<Option<(String, String)>> {
+    let query = "
+    SELECT
+        (SELECT value FROM meta WHERE key = 'current_sheet'),
+        (SELECT value FROM meta WHERE key = 'last_sheet');
+    ";
+
+    let (current, last) = db.query_row(query, [], |row| {
+        Ok((
+            row.get::<usize, Option<String>>(0)?,
+            row.get::<usize, Option<String>>(1)?,
+        ))
+    })?;
+
+    Ok(current.zip(last))
+}
+
+pub fn write_sheet_state(current_sheet: &str, last_sheet: &str, db: &Connection) -> Result<()> {
+    let query = "
+    INSERT INTO meta (key, value) VALUES
+        ('current_sheet', :current),
+        ('last_sheet', :last)
+    ON CONFLICT (key) DO UPDATE SET value = excluded.value;
+    ";
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute(named_params! {
+        ":current": current_sheet,
+        ":last": last_sheet
+    })?;
+
+    Ok(())
+}
+
+pub fn integrity_check(db: &Connection) -> Result<Vec<String>> {
+    let mut stmt = db.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<usize, String>(0))?;
+
+    let mut messages = Vec::new();
+
+    for message in rows {
+        messages.push(message?);
+    }
+
+    Ok(messages)
+}
+
+// The doctor also runs when a migration fails, so it can't rely on the trash column
+fn trash_filter(db: &Connection) -> Result<&'static str> {
+    let query = "SELECT COUNT(*) > 0 FROM pragma_table_info('entries') WHERE name = 'deleted_at'";
+    let has_trash: bool = db.query_row(query, [], |row| row.get(0))?;
+
+    match has_trash {
+        true => Ok("WHERE deleted_at IS NULL"),
+        false => Ok(""),
+    }
+}
+
+// Like `get_all_entries`, but also working before the migrations
+pub fn get_entries_to_check(db: &Connection) -> Result<Vec<Entry>> {
+    let query = format!(
+        "SELECT id, note, start, end, sheet, tags FROM entries {};",
+        trash_filter(db)?
+    );
+
+    let mut stmt = db.prepare(&query)?;
+    let entries = stmt.query_map([], entry_from_row)?;
+
+    entries.collect::<Result<Vec<Entry>, _>>().context("Error while parsing the entries")
+}
+
+// Like `get_all_sheets`, but also working before the migrations
+pub fn get_sheets_to_check(db: &Connection) -> Result<Vec<String>> {
+    let query = format!("SELECT DISTINCT sheet FROM entries {};", trash_filter(db)?);
+
+    let mut stmt = db.prepare(&query)?;
+    let sheets = stmt.query_map([], |row| row.get::<usize, String>(0))?;
+
+    sheets.collect::<Result<Vec<String>, _>>().context("Error while reading the sheets")
+}
+
+// The unparsed start and end of every entry, used to find the broken ones. The entries
+// in the trash are left out, since the broken ones are moved there.
+pub fn get_entry_timestamps(db: &Connection) -> Result<Vec<(usize, String, Option<String>)>> {
+    let query = format!("SELECT id, start, end FROM entries {};", trash_filter(db)?);
+
+    let mut stmt = db.prepare(&query)?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+    let mut timestamps = Vec::new();
+
+    for row in rows {
+        timestamps.push(row?);
+    }
+
+    Ok(timestamps)
+}
+
+pub fn update_entry_timestamp(
+    id: &usize,
+    column: &str,
+    value: DateTime<Local>,
+    db: &Connection,
+) -> Result<()> {
+    let query = match column {
+        "start" => "UPDATE entries SET start = ? WHERE id = ?",
+        "end" => "UPDATE entries SET end = ? WHERE id = ?",
+        _ => return Err(anyhow!("Unknown timestamp column: {}", column)),
+    };
+
+    let mut stmt = db.prepare(query)?;
+    stmt.execute(params![value, id])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_constraint_errors() {
+        let db = Connection::open_in_memory().unwrap();
+        create_tables(&db).unwrap();
+
+        let insert = "INSERT INTO entries (note, start, sheet) VALUES ('a', '2024-03-01', 'work')";
+        db.execute(insert, []).unwrap();
+        let err = anyhow::Error::from(db.execute(insert, []).unwrap_err());
+        assert!(explain_error(&err).unwrap().starts_with("There can only be one running task"));
+
+        let insert = "INSERT INTO templates (name, note) VALUES ('standup', 'Standup')";
+        db.execute(insert, []).unwrap();
+        let err = anyhow::Error::from(db.execute(insert, []).unwrap_err());
+        assert!(explain_error(&err).is_none());
+    }
+
+    #[test]
+    fn test_failed_commit_rolls_back() {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(
+            "PRAGMA foreign_keys = ON;
+             CREATE TABLE parents (id INTEGER PRIMARY KEY);
+             CREATE TABLE children (
+                 parent INTEGER REFERENCES parents (id) DEFERRABLE INITIALLY DEFERRED
+             );",
+        )
+        .unwrap();
+
+        // The missing parent is only noticed by the commit
+        begin_transaction(&db, true).unwrap();
+        db.execute("INSERT INTO children (parent) VALUES (1)", []).unwrap();
+        assert!(commit_transaction(&db).is_err());
+
+        assert!(db.is_autocommit());
+        let count: i64 = db.query_row("SELECT COUNT(*) FROM children", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("pdf-exporter"), "\"pdf-exporter\"");
+        assert_eq!(fts_query("don't"), "\"don't\"");
+        assert_eq!(fts_query("review AND NOT"), "\"review\" \"AND\" \"NOT\"");
+        assert_eq!(fts_query("export* client"), "\"export\"* \"client\"");
+        assert_eq!(fts_query("\"pdf exporter\" bug"), "\"pdf exporter\" \"bug\"");
+        assert_eq!(fts_query("\"unterminated phrase"), "\"unterminated phrase\"");
+        assert_eq!(fts_query("  * \"\" "), "");
+    }
+
+    #[test]
+    fn test_search_entries() {
+        let db = Connection::open_in_memory().unwrap();
+        create_tables(&db).unwrap();
+
+        let notes = ["Fix the pdf-exporter", "don't forget the exporter", "Export the report"];
+        for (i, note) in notes.iter().enumerate() {
+            let entry = Entry {
+                id: None,
+                start: Local::now(),
+                end: Some(Local::now()),
+                name: note.to_string(),
+                sheet: format!("sheet{}", i),
+                tags: Vec::new(),
+            };
+            create_entry(&entry, &db).unwrap();
+        }
+
+        let search = |query: &str| -> Vec<String> {
+            let mut notes: Vec<String> = search_entries(query, None, &db)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.name)
+                .collect();
+            notes.sort();
+            notes
+        };
+
+        assert_eq!(search("pdf-exporter"), vec!["Fix the pdf-exporter"]);
+        assert_eq!(search("don't"), vec!["don't forget the exporter"]);
+        assert_eq!(search("\"the report\""), vec!["Export the report"]);
+        assert_eq!(search("export*").len(), 3);
+        assert!(search("NOT").is_empty());
+    }
+}
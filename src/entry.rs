@@ -10,6 +10,7 @@ pub struct Entry {
     pub end: Option<DateTime<Local>>,
     pub name: String,
     pub sheet: String,
+    pub tags: Vec<String>,
 }
 
 impl Entry {
@@ -20,6 +21,7 @@ impl Entry {
             end: None,
             name: "".to_string(),
             sheet: config.default_sheet.clone(),
+            tags: Vec::new(),
         }
     }
 
@@ -30,6 +32,7 @@ impl Entry {
             end: None,
             name: name.to_string(),
             sheet: sheet.to_string(),
+            tags: Vec::new(),
         }
     }
 
@@ -37,6 +40,14 @@ impl Entry {
         self.end = Some(end);
     }
 
+    // The note followed by the tags, e.g. "code review #backend"
+    pub fn description(&self) -> String {
+        let mut parts = vec![self.name.clone()];
+        parts.extend(self.tags.iter().map(|t| format!("#{}", t)));
+
+        parts.join(" ").trim().to_string()
+    }
+
     pub fn get_duration(&self) -> Duration {
         let end = self.end.unwrap_or(Local::now());
 
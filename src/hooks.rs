@@ -0,0 +1,191 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::io::{stderr, Write};
+use std::process::{Command, ExitStatus, Stdio};
+
+use crate::config::Config;
+use crate::style::{style_string, Styles};
+use crate::Entry;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    PreIn,
+    PostIn,
+    PreOut,
+    PostOut,
+    PostSwitch,
+    PostEdit,
+}
+
+impl Hook {
+    fn name(&self) -> &'static str {
+        match self {
+            Hook::PreIn => "pre-in",
+            Hook::PostIn => "post-in",
+            Hook::PreOut => "pre-out",
+            Hook::PostOut => "post-out",
+            Hook::PostSwitch => "post-switch",
+            Hook::PostEdit => "post-edit",
+        }
+    }
+}
+
+// The commands to run, as written in the "hooks" object of the configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Hooks {
+    pre_in: Option<String>,
+    post_in: Option<String>,
+    pre_out: Option<String>,
+    post_out: Option<String>,
+    post_switch: Option<String>,
+    post_edit: Option<String>,
+}
+
+impl Hooks {
+    fn command(&self, hook: Hook) -> Option<&String> {
+        match hook {
+            Hook::PreIn => self.pre_in.as_ref(),
+            Hook::PostIn => self.post_in.as_ref(),
+            Hook::PreOut => self.pre_out.as_ref(),
+            Hook::PostOut => self.post_out.as_ref(),
+            Hook::PostSwitch => self.post_switch.as_ref(),
+            Hook::PostEdit => self.post_edit.as_ref(),
+        }
+    }
+}
+
+// Where the output of the hooks goes. `t rpc` talks on stdout and `t tui` draws on the
+// terminal, so their hooks can't write there.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum HookOutput {
+    #[default]
+    Inherit,
+    Stderr,
+    Discard,
+}
+
+// A hook that runs once the change is committed, so that it sees the new data
+#[derive(Debug)]
+pub struct PendingHook {
+    pub hook: Hook,
+    pub sheet: String,
+    pub entry: Option<Entry>,
+}
+
+// Runs a hook before a change. If it fails, the change must not happen.
+pub fn run_pre_hook(
+    hook: Hook,
+    sheet: &str,
+    entry: Option<&Entry>,
+    config: &Config,
+    output: HookOutput,
+) -> Result<()> {
+    let Some(status) = run_hook(hook, sheet, entry, config, output)? else {
+        return Ok(());
+    };
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(anyhow!(
+            "The {} hook exited with {}, so nothing was changed.",
+            hook.name(),
+            status
+        )),
+    }
+}
+
+// The change is already done, so a failing hook is only reported
+pub fn run_post_hooks(hooks: &[PendingHook], config: &Config) {
+    for problem in post_hook_problems(hooks, config, HookOutput::Inherit) {
+        println!("{} {}", style_string("Warning:", Styles::Error), problem);
+    }
+}
+
+// Runs the hooks, and tells which ones failed, for the interfaces that report it themselves
+pub fn post_hook_problems(
+    hooks: &[PendingHook],
+    config: &Config,
+    output: HookOutput,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for pending in hooks {
+        let res = run_hook(pending.hook, &pending.sheet, pending.entry.as_ref(), config, output);
+
+        let problem = match res {
+            Ok(Some(status)) if !status.success() => format!("exited with {}", status),
+            Ok(_) => continue,
+            Err(e) => format!("could not run: {:#}", e),
+        };
+
+        problems.push(format!("the {} hook {}", pending.hook.name(), problem));
+    }
+
+    problems
+}
+
+// The entry is given as JSON on stdin, and its main fields as environment variables
+fn run_hook(
+    hook: Hook,
+    sheet: &str,
+    entry: Option<&Entry>,
+    config: &Config,
+    output: HookOutput,
+) -> Result<Option<ExitStatus>> {
+    let Some(command) = config.hooks.command(hook) else {
+        return Ok(None);
+    };
+
+    let mut process = shell(command);
+    process
+        .env("TIMETRACK_HOOK", hook.name())
+        .env("TIMETRACK_SHEET", sheet)
+        .stdin(Stdio::piped());
+
+    match output {
+        HookOutput::Inherit => {}
+        HookOutput::Stderr => {
+            process.stdout(stderr());
+        }
+        HookOutput::Discard => {
+            process.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+    };
+
+    if let Some(entry) = entry {
+        if let Some(id) = entry.id {
+            process.env("TIMETRACK_ID", id.to_string());
+        }
+        if let Some(end) = entry.end {
+            process.env("TIMETRACK_END", end.to_rfc3339());
+        }
+        process
+            .env("TIMETRACK_NOTE", &entry.name)
+            .env("TIMETRACK_START", entry.start.to_rfc3339())
+            .env("TIMETRACK_TAGS", entry.tags.join(","));
+    }
+
+    let mut child = process
+        .spawn()
+        .context(format!("Cannot run the {} hook: {}", hook.name(), command))?;
+
+    // A hook that doesn't read its input is fine
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "{}", serde_json::to_string(&entry)?);
+    }
+
+    Ok(Some(child.wait()?))
+}
+
+fn shell(command: &str) -> Command {
+    let (program, flag) = match cfg!(windows) {
+        true => ("cmd", "/C"),
+        false => ("sh", "-c"),
+    };
+
+    let mut process = Command::new(program);
+    process.args([flag, command]);
+
+    process
+}
@@ -1,18 +1,33 @@
+mod binding;
+mod cache;
+mod chart;
 mod commands;
 mod config;
 mod database;
 mod entry;
+mod hooks;
+mod range;
 mod state;
 mod style;
+mod template;
 mod utils;
 
 use anyhow::{Result, Context};
-use clap::{Args, Parser, Subcommand};
+use chrono::{DateTime, Local};
+use cache::PromptCache;
+use chart::ChartBy;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, CompleteEnv, Shell};
 use commands::*;
 use config::Config;
-use database::{connect_to_db, create_tables, ensure_db_exists};
+use database::{
+    begin_transaction, commit_transaction, connect_to_db, create_tables, ensure_db_exists,
+    explain_error,
+};
 pub use entry::Entry;
+use hooks::{run_post_hooks, Hooks};
 use langtime::parse;
+use range::DateRange;
 pub use state::State;
 
 use crate::style::{style_string, Styles};
@@ -22,6 +37,9 @@ use crate::style::{style_string, Styles};
 struct Cli {
     #[command(subcommand)]
     command: Subcommands,
+    /// Don't run the hooks set in the configuration file
+    #[arg(long, global = true)]
+    no_hooks: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -29,6 +47,7 @@ enum Subcommands {
     /// Checks into the current timesheet
     In {
         /// The task description
+        #[arg(add = ArgValueCandidates::new(complete_notes))]
         task: Option<String>,
         /// The time and date this task was started. "15 minutes ago" and similar are also ok
         #[arg(short, long)]
@@ -36,13 +55,53 @@ enum Subcommands {
         /// If a task is currently ongoing, it ends it and starts a new task.
         /// If the "at" parameter is used, it will switch to the new task at that specific time
         #[arg(short, long)]
-        switch: bool
+        switch: bool,
+        /// The timesheet to check into, instead of the current one
+        #[arg(long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+        /// A tag for this task. Can be repeated
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Check in even if the sheet is archived
+        #[arg(long)]
+        force: bool,
+    },
+    /// Adds a task that is already over, e.g. `t log @standup --at 9:30`
+    Log {
+        /// The task description, or a template like "@standup"
+        task: String,
+        /// When the task started. By default, it ends now
+        #[arg(short, long)]
+        at: Option<String>,
+        /// How long the task lasted, e.g. "15m". Overrides the template's duration
+        #[arg(short, long)]
+        duration: Option<String>,
+        /// The timesheet to add the task to, instead of the current one
+        #[arg(long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+        /// A tag for this task. Can be repeated
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Add the task even if the sheet is archived
+        #[arg(long)]
+        force: bool,
+    },
+    /// Manages the templates of the tasks started often, used as `t in @name`
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
     },
     /// Checks out of the current timesheet
     Out {
         /// The time and date this task has ended. "15 minutes ago" and similar are also ok
         #[arg(short, long)]
         at: Option<String>,
+        /// The timesheet to check out of, instead of the current one
+        #[arg(long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+        /// Check out even if the sheet is archived
+        #[arg(long)]
+        force: bool,
     },
     /// Displays the current timesheet
     Display {
@@ -53,15 +112,35 @@ enum Subcommands {
         #[arg(short, long)]
         ids: bool,
         /// Filter the tasks based on when they started
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "range")]
         start: Option<String>,
         /// Filter the tasks based on when they ended
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "range")]
         end: Option<String>,
         /// Just filter by whole days, do not take into account the time
         #[arg(short, long)]
         filter_by_date: bool,
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Display every timesheet
+        #[arg(short, long, conflicts_with = "sheet")]
+        all: bool,
+        /// With --all, also display the archived timesheets
+        #[arg(long, requires = "all")]
+        archived: bool,
+        /// Also draw a chart of the hours per day, or per sheet or task with --chart=sheet or
+        /// --chart=note
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "day",
+            value_name = "BY"
+        )]
+        chart: Option<ChartBy>,
         /// The timesheet to display, or the current one
+        #[arg(add = ArgValueCandidates::new(complete_sheets))]
         sheet: Option<String>,
     },
     /// Like `Display`, but for a specific month, or the current one
@@ -73,23 +152,109 @@ enum Subcommands {
         #[arg(short, long)]
         ids: bool,
         /// The specific month to show. The format is yyyy-mm (e.g. "2024-03")
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "range")]
         month: Option<String>,
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Also draw a chart of the hours per day, or per sheet or task with --chart=sheet or
+        /// --chart=note
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "day",
+            value_name = "BY"
+        )]
+        chart: Option<ChartBy>,
         /// The timesheet to display, or the current one
+        #[arg(add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+    },
+    /// Searches the task descriptions, showing the best matches first
+    Search {
+        /// The words to look for. Use quotes for phrases, e.g. '"pdf export"', and * for prefixes, e.g. 'export*'
+        query: String,
+        /// Only search in this timesheet
+        #[arg(long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+        /// Only show the tasks started after this time
+        #[arg(short, long, conflicts_with = "range")]
+        start: Option<String>,
+        /// Only show the tasks started before this time
+        #[arg(short, long, conflicts_with = "range")]
+        end: Option<String>,
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Show the tasks IDs
+        #[arg(short, long)]
+        ids: bool,
+    },
+    /// Like `Display`, but for a week, with the time of each day
+    Week {
+        /// Any day of the week to show, or its ISO number (e.g. "2024-W12"). Defaults to this week
+        #[arg(short, long)]
+        week: Option<String>,
+        /// Move by this number of weeks, e.g. -1 for the previous one
+        #[arg(short, long, default_value_t = 0, allow_negative_numbers = true)]
+        offset: i64,
+        /// Show the hours of every sheet in each day, instead of the tasks
+        #[arg(short, long)]
+        matrix: bool,
+        /// Also draw a chart of the hours per day, or per sheet or task with --chart=sheet or
+        /// --chart=note
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "day",
+            value_name = "BY"
+        )]
+        chart: Option<ChartBy>,
+        /// Show the tasks IDs
+        #[arg(short, long)]
+        ids: bool,
+        /// The timesheet to display, or the current one
+        #[arg(conflicts_with = "matrix", add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+    },
+    /// Shows the hours of each month in a year, and a calendar of the daily hours
+    Year {
+        /// The year to show, or the current one
+        year: Option<i32>,
+        /// Only count the tasks of this timesheet
+        #[arg(long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
         sheet: Option<String>,
     },
     /// Change timesheet
+    #[command(alias = "s")]
     Sheet {
+        #[arg(add = ArgValueCandidates::new(complete_sheets))]
         name: String,
         #[arg(short, long)]
         rename: Option<String>,
+        /// Bind the sheet to the current directory, by writing a .timetrack file
+        #[arg(short, long, conflicts_with = "rename")]
+        bind: bool,
+        /// Archive the sheet, hiding it from the lists and making it read-only
+        #[arg(long, conflicts_with_all = ["rename", "bind"])]
+        archive: bool,
+        /// Bring an archived sheet back
+        #[arg(long, conflicts_with_all = ["rename", "bind", "archive"])]
+        unarchive: bool,
     },
     /// List available timesheet
-    List,
+    #[command(alias = "l")]
+    List {
+        /// Also list the archived timesheets
+        #[arg(long)]
+        archived: bool,
+    },
     /// Edit a task
     Edit {
         /// The ID of the task to edit
-        #[arg(short, long)]
+        #[arg(short, long, add = ArgValueCandidates::new(complete_ids))]
         id: Option<usize>,
         /// Set a new start date and time for this task
         #[arg(short, long)]
@@ -98,60 +263,408 @@ enum Subcommands {
         #[arg(short, long)]
         end: Option<String>,
         /// Move this task to a different timesheet
-        #[arg(short, long)]
+        #[arg(short, long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
         move_to: Option<String>,
         /// The new task description
         notes: Option<String>,
+        /// Edit the task even if its sheet is archived
+        #[arg(long)]
+        force: bool,
     },
+    /// Splits a task in two at a point in time
+    Split {
+        /// The ID of the task to split, or the running one
+        #[arg(short, long, add = ArgValueCandidates::new(complete_ids))]
+        id: Option<usize>,
+        /// When the second part starts. "15 minutes ago" and similar are also ok
+        #[arg(short, long)]
+        at: String,
+        /// The description of the second part, if different
+        #[arg(short, long)]
+        note: Option<String>,
+        /// Split the task even if its sheet is archived
+        #[arg(long)]
+        force: bool,
+    },
+    /// Merges tasks into a single one, spanning all of them
+    Merge {
+        /// The ID of a task to merge. Must be repeated for each task
+        #[arg(
+            long = "id",
+            required_unless_present = "auto",
+            conflicts_with = "auto",
+            add = ArgValueCandidates::new(complete_ids)
+        )]
+        ids: Vec<usize>,
+        /// Find and merge the consecutive tasks with the same description, after a preview
+        #[arg(long)]
+        auto: bool,
+        /// With --auto, the longest pause between two tasks that are merged
+        #[arg(long, default_value = "2m", requires = "auto")]
+        gap: String,
+        /// With --auto, the timesheet to look into, instead of the current one
+        #[arg(
+            long,
+            value_name = "SHEET",
+            add = ArgValueCandidates::new(complete_sheets),
+            requires = "auto"
+        )]
+        sheet: Option<String>,
+        /// With --auto, the day to look into, instead of today
+        #[arg(long, requires = "auto")]
+        day: Option<String>,
+        /// Merge the tasks even if their sheet is archived
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists the untracked time within the working hours, and offers to fill it
+    Gaps {
+        /// The day to check, instead of today
+        #[arg(short, long)]
+        day: Option<String>,
+        /// The shortest gap to show
+        #[arg(short, long, default_value = "10m")]
+        min: String,
+    },
+    /// Draws the tasks of a day on a ruler, one line for each sheet
+    Timeline {
+        /// The day to show, instead of today
+        #[arg(short, long)]
+        day: Option<String>,
+        /// Show all the 24 hours, instead of the working hours
+        #[arg(short, long)]
+        full: bool,
+    },
+    /// Serves a JSON API to read and change the tasks, e.g. for dashboards and editor plugins
+    Serve {
+        /// The port to listen on
+        #[arg(short, long, default_value_t = 7777)]
+        port: u16,
+        /// The address to listen on. Only this machine can connect by default
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Require this bearer token on every request. Can also be set with TIMETRACK_TOKEN
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Speaks JSON-RPC 2.0 on stdin and stdout, one message per line, e.g. for editor plugins
+    Rpc,
+    /// Opens a full-screen interface to browse and edit the tasks
+    Tui,
     /// Shows the active task for the current sheet
-    Current,
+    #[command(alias = "c")]
+    Current {
+        /// Keep the output open and refresh it every second, until Ctrl-C is pressed
+        #[arg(short, long)]
+        watch: bool,
+    },
+    /// Prints the active task in a format suitable for status bars
+    Status {
+        /// The status bar the output is meant for
+        #[arg(short, long, value_enum, default_value_t = StatusFormat::Plain)]
+        format: StatusFormat,
+        /// The text to show, e.g. "{sheet}: {note} {elapsed}". Overrides the configuration
+        #[arg(short, long)]
+        template: Option<String>,
+    },
+    /// Prints a compact segment with the running task, meant for the shell prompt
+    Prompt,
+    /// Generates the completion script for a shell
+    Completions {
+        shell: Shell,
+    },
+    /// Saves a copy of the database, by default with the automatic snapshots
+    #[command(args_conflicts_with_subcommands = true)]
+    Backup {
+        #[command(subcommand)]
+        action: Option<BackupAction>,
+        /// Where to save the copy of the database
+        path: Option<String>,
+    },
+    /// Replaces the database with a backup, after checking its integrity
+    Restore {
+        /// The backup file to restore
+        path: String,
+    },
+    /// Checks the database for broken data, and offers to repair it
+    Doctor {
+        /// Repair every problem without asking
+        #[arg(long)]
+        fix: bool,
+    },
     /// Removes a task or a whole timesheet
     Kill {
         #[command(flatten)]
         kill_args: KillArgs,
+        /// Don't ask for confirmation. The removed tasks can still be restored from the trash
+        #[arg(short, long)]
+        yes: bool,
+        /// Remove it even if the sheet is archived
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists, restores or permanently removes the killed tasks
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum BackupAction {
+    /// Lists the saved snapshots
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+enum TemplateAction {
+    /// Saves a template, or replaces the one with the same name
+    Add {
+        /// The name of the template, used as `t in @name`
+        name: String,
+        /// The task description. By default, the name of the template
+        #[arg(short, long)]
+        note: Option<String>,
+        /// The timesheet of the task, instead of the current one
+        #[arg(long, value_name = "SHEET", add = ArgValueCandidates::new(complete_sheets))]
+        sheet: Option<String>,
+        /// A tag for the task. Can be repeated
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// How long the task usually lasts, used by `t log`, e.g. "15m"
+        #[arg(short, long)]
+        duration: Option<String>,
+    },
+    /// Lists the templates
+    List,
+    /// Removes a template
+    Remove {
+        /// The name of the template to remove
+        name: String,
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum TrashAction {
+    /// Lists the tasks in the trash
+    List,
+    /// Brings a task back from the trash
+    Restore {
+        /// The ID of the task to restore
+        id: usize,
+        /// Restore the task even if its sheet is archived
+        #[arg(long)]
+        force: bool,
+    },
+    /// Permanently removes the tasks in the trash
+    Purge {
+        /// Only remove the tasks killed more than this long ago, e.g. "30d"
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+// The optional start and end used to filter the tasks
+type Bounds = (Option<DateTime<Local>>, Option<DateTime<Local>>);
+
+// The period filter shared by the commands that show tasks
+#[derive(Args, Debug)]
+struct RangeArgs {
+    /// Only show the tasks started in this period, e.g. "today", "last week", "2024-Q1",
+    /// "2024-W12" or "from 2024-01 to 2024-03"
+    #[arg(short, long)]
+    range: Option<String>,
+}
+
+impl RangeArgs {
+    // The start and the end of the range, or the given ones if there is no range
+    fn bounds(
+        &self,
+        start: &Option<String>,
+        end: &Option<String>,
+        config: &Config,
+    ) -> Result<Bounds> {
+        if let Some(range) = &self.range {
+            let range = DateRange::parse(range, config.week_start)?;
+            return Ok((Some(range.start), Some(range.end)));
+        }
+
+        Ok((
+            start.as_ref().map(|s| parse(s)).transpose()?,
+            end.as_ref().map(|e| parse(e)).transpose()?,
+        ))
+    }
+}
+
 #[derive(Args, Debug)]
 #[group(required = true, multiple = false)]
 struct KillArgs {
     /// The ID of the task to remove
-    #[arg(long)]
+    #[arg(long, add = ArgValueCandidates::new(complete_ids))]
     id: Option<usize>,
     /// The name of the timesheet to remove
+    #[arg(add = ArgValueCandidates::new(complete_sheets))]
     sheet: Option<String>,
 }
 
 fn main() {
+    // When called by the shell completions, this prints them and exits
+    CompleteEnv::with_factory(Cli::command).var(COMPLETE_VAR).complete();
+
     if let Err(e) = cli() {
         println!("{} {}", style_string("Error:", Styles::Error), e);
+
+        for cause in e.chain().skip(1) {
+            println!("  {}", cause);
+        }
+
+        if let Some(explanation) = explain_error(&e) {
+            println!("{}", explanation);
+        }
         std::process::exit(1);
     }
 }
 
 fn cli() -> Result<()> {
-    let config = Config::build().context("Could not load program configuration.")?;
+    let mut config = Config::build().context("Could not load program configuration.")?;
+
+    let cli = Cli::parse();
 
-    setup(&config).context("Could not prepare the program database.")?;
+    if cli.no_hooks {
+        config.hooks = Hooks::default();
+    }
+
+    // The prompt runs on every shell prompt, so it skips the database when the cache is fresh
+    if let Subcommands::Prompt = cli.command {
+        if print_cached_prompt(&config) {
+            return Ok(());
+        }
+    }
+
+    let setup_res = setup(&config).context("Could not prepare the program database.");
+
+    // The doctor must also work on databases that are too broken to be migrated
+    match setup_res {
+        Err(e) if matches!(cli.command, Subcommands::Doctor { .. }) => {
+            println!("{} {:#}", style_string("Warning:", Styles::Error), e);
+        }
+        res => res?,
+    };
 
     let mut state = State::build(&config).context("Could not load the program state.")?;
 
-    let cli = Cli::parse();
+    let destructive = matches!(
+        cli.command,
+        Subcommands::Kill { .. }
+            | Subcommands::Trash {
+                action: TrashAction::Purge { .. }
+            }
+    );
+    auto_snapshot(destructive, &config, &state).context("Could not take the automatic snapshot.")?;
+
+    // The watch mode, the full-screen interface and the server keep running, so they must
+    // not hold a transaction open.
+    // Backups and restores copy the whole database, outside of transactions.
+    // The doctor and the gaps wait for the user between changes, so they apply them one by one.
+    let in_transaction = !matches!(
+        cli.command,
+        Subcommands::Current { watch: true }
+            | Subcommands::Tui
+            | Subcommands::Serve { .. }
+            | Subcommands::Rpc
+            | Subcommands::Backup { .. }
+            | Subcommands::Restore { .. }
+            | Subcommands::Doctor { .. }
+            | Subcommands::Gaps { .. }
+    );
+
+    // Commands that change the data lock the database from the start, so that
+    // concurrent commands wait for each other instead of failing midway.
+    // In and out run the pre-hooks, and merge, kill and trash may ask for a confirmation:
+    // they must not block the other commands while waiting, so they take the lock with
+    // their first change, which fails if the data was changed by someone else in the meantime.
+    let writes = matches!(
+        cli.command,
+        Subcommands::Log { .. }
+            | Subcommands::Template { .. }
+            | Subcommands::Sheet { .. }
+            | Subcommands::Edit { .. }
+            | Subcommands::Split { .. }
+    );
+
+    if in_transaction {
+        begin_transaction(&state.database, writes)?;
+    }
 
     match &cli.command {
-        Subcommands::In { task, at, switch } => {
+        Subcommands::In {
+            task,
+            at,
+            switch,
+            sheet,
+            tags,
+            force,
+        } => {
             let target_time = at.as_ref().map(|at| parse(at)).transpose()?;
 
             let task = task.as_ref();
             let default_task = "".to_string();
             let task = task.unwrap_or(&default_task);
 
-            start_task(task, target_time, switch, &state).context("Could not start task.")?;
+            // Templates like "@standup" fill in the note, the sheet and the tags
+            if let Some(args) = expand_task(task, sheet.as_ref(), tags, &state)? {
+                start_task(
+                    &args.note,
+                    target_time,
+                    switch,
+                    args.sheet.as_ref(),
+                    &args.tags,
+                    force,
+                    &config,
+                    &mut state,
+                )
+                .context("Could not start task.")?;
+            }
         }
-        Subcommands::Out { at } => {
+        Subcommands::Log {
+            task,
+            at,
+            duration,
+            sheet,
+            tags,
+            force,
+        } => {
+            let at = at.as_ref().map(|at| parse(at)).transpose()?;
+
+            if let Some(args) = expand_task(task, sheet.as_ref(), tags, &state)? {
+                log_task(args, at, duration.as_ref(), force, &state)
+                    .context("Could not log the task.")?;
+            }
+        }
+        Subcommands::Template { action } => match action {
+            TemplateAction::Add {
+                name,
+                note,
+                sheet,
+                tags,
+                duration,
+            } => add_template(name, note.as_ref(), sheet.as_ref(), tags, duration.as_ref(), &state)
+                .context("Could not save the template.")?,
+            TemplateAction::List => {
+                list_templates(&state).context("Could not list the templates.")?
+            }
+            TemplateAction::Remove { name } => {
+                remove_template(name, &state).context("Could not remove the template.")?
+            }
+        },
+        Subcommands::Out { at, sheet, force } => {
             let target_time = at.as_ref().map(|at| parse(at)).transpose()?;
 
-            stop_task(target_time, &mut state).context("Could not stop task.")?;
+            stop_task(target_time, sheet.as_ref(), force, &config, &mut state)
+                .context("Could not stop task.")?;
         }
         Subcommands::Display {
             json,
@@ -160,14 +673,24 @@ fn cli() -> Result<()> {
             end,
             filter_by_date,
             ids,
+            all,
+            archived,
+            range,
+            chart,
         } => {
+            let sheets = match all {
+                true => SheetFilter::All { archived: *archived },
+                false => SheetFilter::Sheet(sheet.as_ref()),
+            };
+            let (start, end) = range.bounds(start, end, &config)?;
+
             display_tasks(
                 json,
-                sheet.as_ref(),
-                start.as_ref().map(|s| parse(s)).transpose()?,
-                end.as_ref().map(|e| parse(e)).transpose()?,
+                sheets,
+                start,
+                end,
                 filter_by_date,
-                ids,
+                &display_options(ids, chart),
                 &state,
             ).context("Could not display tasks.")?;
         }
@@ -176,18 +699,78 @@ fn cli() -> Result<()> {
             ids,
             month,
             sheet,
+            range,
+            chart,
+        } => {
+            let period = month.as_ref().or(range.range.as_ref());
+            let options = display_options(ids, chart);
+
+            display_month(json, &options, period, sheet.as_ref(), &config, &mut state).context("Could not display months")?;
+        }
+        Subcommands::Week {
+            week,
+            offset,
+            matrix,
+            ids,
+            sheet,
+            chart,
         } => {
-            display_month(json, ids, month.as_ref(), sheet.as_ref(), &mut state).context("Could not display months")?;
+            let options = display_options(ids, chart);
+
+            display_week(week.as_ref(), offset, matrix, sheet.as_ref(), &options, &config, &state)
+                .context("Could not display the week.")?;
         }
-        Subcommands::Sheet { name, rename } => match rename {
+        Subcommands::Year { year, sheet } => {
+            display_year(*year, sheet.as_ref(), &config, &state).context("Could not display the year.")?;
+        }
+        Subcommands::Search {
+            query,
+            sheet,
+            start,
+            end,
+            ids,
+            range,
+        } => {
+            let (start, end) = range.bounds(start, end, &config)?;
+
+            search_tasks(
+                query,
+                sheet.as_ref(),
+                start,
+                end,
+                ids,
+                &state,
+            )
+            .context("Could not search tasks.")?;
+        }
+        Subcommands::Sheet {
+            name,
+            rename,
+            bind,
+            archive,
+            unarchive,
+        } => match rename {
+            None if *bind => bind_sheet(name, &mut state).context("Could not bind sheet.")?,
+            None if *archive || *unarchive => {
+                archive_sheet(name, *archive, &state).context("Could not archive sheet.")?
+            }
             None => checkout_sheet(name, &mut state).context("Could not checkout sheet.")?,
             Some(new_name) => rename_sheet(name, new_name, &mut state).context("Could not rename sheet.")?,
         },
-        Subcommands::List => {
-            list_sheets(&state).context("Could not list sheets.")?;
+        Subcommands::List { archived } => {
+            list_sheets(archived, &state).context("Could not list sheets.")?;
+        }
+        Subcommands::Current { watch } => {
+            current_task(watch, &config, &state).context("Could not get current task.")?;
+        }
+        Subcommands::Status { format, template } => {
+            print_status(format, template.as_ref(), &config, &state).context("Could not get the status.")?;
+        }
+        Subcommands::Prompt => {
+            print_prompt(&config, &state).context("Could not print the prompt.")?;
         }
-        Subcommands::Current => {
-            current_task(&state).context("Could not get current task.")?;
+        Subcommands::Completions { shell } => {
+            print_completions(*shell).context("Could not print the completion script.")?;
         }
         Subcommands::Edit {
             id,
@@ -195,21 +778,104 @@ fn cli() -> Result<()> {
             end,
             move_to,
             notes,
+            force,
         } => {
-            edit_task(id, start, end, move_to, notes, &mut state).context("Could not edit task.")?;
+            edit_task(id, start, end, move_to, notes, force, &mut state).context("Could not edit task.")?;
+        }
+        Subcommands::Split { id, at, note, force } => {
+            split_task(id, parse(at)?, note.as_ref(), force, &state).context("Could not split task.")?;
+        }
+        Subcommands::Merge {
+            ids,
+            auto,
+            gap,
+            sheet,
+            day,
+            force,
+        } => match auto {
+            true => {
+                let day = day.as_ref().map(|d| parse(d)).transpose()?;
+
+                auto_merge_tasks(gap, sheet.as_ref(), day, force, &state).context("Could not merge tasks.")?
+            }
+            false => merge_tasks(ids, force, &state).context("Could not merge tasks.")?,
+        },
+        Subcommands::Gaps { day, min } => {
+            let day = day.as_ref().map(|d| parse(d)).transpose()?;
+
+            fill_gaps(day, min, &config, &state).context("Could not check the gaps.")?;
+        }
+        Subcommands::Serve { port, host, token } => {
+            serve(host, port, token.as_ref(), &config, &mut state).context("Could not run the server.")?;
+        }
+        Subcommands::Rpc => {
+            run_rpc(&config, &mut state).context("Could not run the JSON-RPC interface.")?;
+        }
+        Subcommands::Tui => {
+            run_tui(&config, &state).context("Could not run the full-screen interface.")?;
         }
-        Subcommands::Kill { kill_args } => {
+        Subcommands::Timeline { day, full } => {
+            let day = day.as_ref().map(|d| parse(d)).transpose()?;
+
+            display_timeline(day, full, &config, &state).context("Could not display the timeline.")?;
+        }
+        Subcommands::Backup { action, path } => match action {
+            Some(BackupAction::List) => list_snapshots(&config).context("Could not list the snapshots.")?,
+            None => backup_database(path.as_ref(), &config, &state).context("Could not back up the database.")?,
+        },
+        Subcommands::Restore { path } => {
+            restore_database(path, &config, &mut state).context("Could not restore the database.")?;
+        }
+        Subcommands::Doctor { fix } => {
+            run_doctor(fix, &config, &mut state).context("Could not check the database.")?;
+        }
+        Subcommands::Kill {
+            kill_args,
+            yes,
+            force,
+        } => {
             if let Some(id) = &kill_args.id {
-                kill_task(id, &mut state).context("Could not delete this task.")?;
+                kill_task(id, yes, force, &mut state).context("Could not delete this task.")?;
             } else if let Some(sheet) = &kill_args.sheet {
-                kill_sheet(sheet, &mut state).context("Could not delete the timesheet.")?;
+                kill_sheet(sheet, yes, force, &mut state).context("Could not delete the timesheet.")?;
             }
         }
+        Subcommands::Trash { action } => match action {
+            TrashAction::List => list_trash(&state).context("Could not list the trash.")?,
+            TrashAction::Restore { id, force } => {
+                restore_from_trash(id, force, &state).context("Could not restore the task.")?
+            }
+            TrashAction::Purge { older_than, yes } => {
+                purge_trash(older_than.as_ref(), yes, &state).context("Could not purge the trash.")?
+            }
+        },
     };
 
+    if in_transaction {
+        commit_transaction(&state.database)?;
+    }
+
+    run_post_hooks(&state.pending_hooks, &config);
+
+    // Keep the prompt cache in sync with the commands that change the active task
+    if let Subcommands::In { .. } | Subcommands::Out { .. } | Subcommands::Sheet { .. } = cli.command {
+        PromptCache::build(&state)?
+            .save(&config)
+            .context("Could not update the prompt cache.")?;
+    }
+
     Ok(())
 }
 
+// The options shared by the commands that show a table of tasks
+fn display_options(ids: &bool, chart: &Option<ChartBy>) -> ReadableOptions {
+    let mut options = ReadableOptions::complete();
+    options.show_ids = *ids;
+    options.chart = *chart;
+
+    options
+}
+
 fn setup(config: &Config) -> Result<()> {
     ensure_db_exists(config)?;
     let db = connect_to_db(config)?;
@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Weekday};
+
+use crate::utils::{day_begin, day_end, get_month_boundaries};
+
+// A span of time, from the beginning of its first day to the end of its last one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl DateRange {
+    // Accepts "today", "yesterday", "this week", "last month", "this year",
+    // "2024", "2024-Q1", "2024-W12", "2024-03", "2024-03-01", "from X to Y",
+    // and any single day understood by langtime, e.g. "3 days ago". The weeks
+    // start on the given day.
+    pub fn parse(s: &str, week_start: Weekday) -> Result<DateRange> {
+        DateRange::parse_at(s, Local::now(), week_start)
+    }
+
+    // The week that contains the given day
+    pub fn week(day: NaiveDate, week_start: Weekday) -> Result<DateRange> {
+        let first = day.week(week_start).first_day();
+        DateRange::days(first, first + Duration::days(6))
+    }
+
+    fn parse_at(s: &str, now: DateTime<Local>, week_start: Weekday) -> Result<DateRange> {
+        let s = s.trim().to_lowercase();
+        let today = now.date_naive();
+
+        if let Some(rest) = s.strip_prefix("from ") {
+            let (from, to) = rest
+                .split_once(" to ")
+                .ok_or(anyhow!("Expected \"from ... to ...\", found: {}", s))?;
+
+            let from = DateRange::parse_at(from, now, week_start)?;
+            let to = DateRange::parse_at(to, now, week_start)?;
+
+            if to.end < from.start {
+                return Err(anyhow!("The range ends before it starts: {}", s));
+            }
+
+            return Ok(DateRange {
+                start: from.start,
+                end: to.end,
+            });
+        }
+
+        match s.as_str() {
+            "today" => return DateRange::days(today, today),
+            "yesterday" => {
+                let yesterday = today - Duration::days(1);
+                return DateRange::days(yesterday, yesterday);
+            }
+            "this week" => return DateRange::week(today, week_start),
+            "last week" => return DateRange::week(today - Duration::weeks(1), week_start),
+            "this month" => return DateRange::month(today.year(), today.month()),
+            "last month" => {
+                let last_month = today.with_day(1).unwrap() - Duration::days(1);
+                return DateRange::month(last_month.year(), last_month.month());
+            }
+            "this year" => return DateRange::year(today.year()),
+            "last year" => return DateRange::year(today.year() - 1),
+            _ => {}
+        };
+
+        if let Some(range) = DateRange::parse_calendar(&s, week_start)? {
+            return Ok(range);
+        }
+
+        // Anything else is a single day
+        match langtime::parse(&s) {
+            Ok(dt) => DateRange::days(dt.date_naive(), dt.date_naive()),
+            Err(_) => Err(anyhow!(
+                "Invalid range: {}. Try \"today\", \"last week\", \"2024-Q1\", \"2024-W12\" or \"from monday to today\".",
+                s
+            )),
+        }
+    }
+
+    // The calendar periods: "2024", "2024-Q1", "2024-W12", "2024-03" and "2024-03-01".
+    // With weeks that don't start on Monday, "2024-W12" is the week that contains
+    // the Monday of the ISO week.
+    fn parse_calendar(s: &str, week_start: Weekday) -> Result<Option<DateRange>> {
+        let (year, rest) = match s.split_once('-') {
+            Some((year, rest)) => (year, Some(rest)),
+            None => (s, None),
+        };
+
+        let Ok(year) = year.parse::<i32>() else {
+            return Ok(None);
+        };
+
+        let Some(rest) = rest else {
+            return DateRange::year(year).map(Some);
+        };
+
+        if let Some(quarter) = rest.strip_prefix('q') {
+            let quarter: u32 = quarter
+                .parse()
+                .map_err(|_| anyhow!("Invalid quarter: {}", s))?;
+
+            if !(1..=4).contains(&quarter) {
+                return Err(anyhow!("The quarter must be between 1 and 4: {}", s));
+            }
+
+            let first = DateRange::month(year, quarter * 3 - 2)?;
+            let last = DateRange::month(year, quarter * 3)?;
+
+            return Ok(Some(DateRange {
+                start: first.start,
+                end: last.end,
+            }));
+        }
+
+        if let Some(week) = rest.strip_prefix('w') {
+            let week: u32 = week.parse().map_err(|_| anyhow!("Invalid week: {}", s))?;
+            let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                .ok_or(anyhow!("Invalid week: {}", s))?;
+
+            return DateRange::week(monday, week_start).map(Some);
+        }
+
+        match rest.split_once('-') {
+            None => {
+                let (start, end) = get_month_boundaries(s)?;
+                Ok(Some(DateRange {
+                    start,
+                    end: day_end(end),
+                }))
+            }
+            Some(_) => {
+                let day = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .map_err(|_| anyhow!("Invalid date: {}", s))?;
+                DateRange::days(day, day).map(Some)
+            }
+        }
+    }
+
+    fn days(first: NaiveDate, last: NaiveDate) -> Result<DateRange> {
+        let start = Local
+            .from_local_datetime(&first.and_hms_opt(12, 0, 0).unwrap())
+            .earliest()
+            .ok_or(anyhow!("Invalid date: {}", first))?;
+        let end = Local
+            .from_local_datetime(&last.and_hms_opt(12, 0, 0).unwrap())
+            .earliest()
+            .ok_or(anyhow!("Invalid date: {}", last))?;
+
+        Ok(DateRange {
+            start: day_begin(start),
+            end: day_end(end),
+        })
+    }
+
+    fn month(year: i32, month: u32) -> Result<DateRange> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)
+            .ok_or(anyhow!("Invalid month: {}-{}", year, month))?;
+        let next = match month {
+            12 => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+            _ => NaiveDate::from_ymd_opt(year, month + 1, 1),
+        }
+        .ok_or(anyhow!("Invalid month: {}-{}", year, month))?;
+
+        DateRange::days(first, next - Duration::days(1))
+    }
+
+    fn year(year: i32) -> Result<DateRange> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1).ok_or(anyhow!("Invalid year: {}", year))?;
+        let last = NaiveDate::from_ymd_opt(year, 12, 31).ok_or(anyhow!("Invalid year: {}", year))?;
+
+        DateRange::days(first, last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn check(s: &str, first: NaiveDate, last: NaiveDate) {
+        check_from(s, Weekday::Mon, first, last);
+    }
+
+    fn check_from(s: &str, week_start: Weekday, first: NaiveDate, last: NaiveDate) {
+        // Wednesday
+        let now = Local.with_ymd_and_hms(2024, 3, 13, 10, 0, 0).unwrap();
+        let range = DateRange::parse_at(s, now, week_start).unwrap();
+
+        assert_eq!(range.start.date_naive(), first, "start of {}", s);
+        assert_eq!(range.end.date_naive(), last, "end of {}", s);
+        assert_eq!(range.start, day_begin(range.start));
+        assert_eq!(range.end, day_end(range.end));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        check("today", date(2024, 3, 13), date(2024, 3, 13));
+        check("Yesterday", date(2024, 3, 12), date(2024, 3, 12));
+        check("this week", date(2024, 3, 11), date(2024, 3, 17));
+        check("last week", date(2024, 3, 4), date(2024, 3, 10));
+        check("this month", date(2024, 3, 1), date(2024, 3, 31));
+        check("last month", date(2024, 2, 1), date(2024, 2, 29));
+        check("last year", date(2023, 1, 1), date(2023, 12, 31));
+        check("2024", date(2024, 1, 1), date(2024, 12, 31));
+        check("2024-Q1", date(2024, 1, 1), date(2024, 3, 31));
+        check("2024-q4", date(2024, 10, 1), date(2024, 12, 31));
+        check("2024-W12", date(2024, 3, 18), date(2024, 3, 24));
+        check("2024-02", date(2024, 2, 1), date(2024, 2, 29));
+        check("2024-02-10", date(2024, 2, 10), date(2024, 2, 10));
+        check("from 2024-01 to 2024-Q2", date(2024, 1, 1), date(2024, 6, 30));
+        check("from last week to today", date(2024, 3, 4), date(2024, 3, 13));
+
+        // Weeks starting on Sunday
+        check_from("this week", Weekday::Sun, date(2024, 3, 10), date(2024, 3, 16));
+        check_from("2024-W12", Weekday::Sun, date(2024, 3, 17), date(2024, 3, 23));
+    }
+
+    #[test]
+    fn test_parse_invalid_range() {
+        let now = Local.with_ymd_and_hms(2024, 3, 13, 10, 0, 0).unwrap();
+
+        for s in [
+            "2024-Q5",
+            "2024-W60",
+            "2024-13",
+            "2024-xx",
+            "2024-02-30",
+            "from today",
+            "from today to last week",
+            "not a date",
+        ] {
+            assert!(
+                DateRange::parse_at(s, now, Weekday::Mon).is_err(),
+                "{} should be invalid",
+                s
+            );
+        }
+    }
+}
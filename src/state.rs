@@ -3,66 +3,65 @@ use directories::ProjectDirs;
 use rusqlite::Connection;
 use std::fs;
 
+use crate::binding::Binding;
 use crate::config::Config;
-use crate::database::connect_to_db;
+use crate::database::{connect_to_db, read_sheet_state, write_sheet_state};
+use crate::hooks::{HookOutput, PendingHook};
+use crate::style::{style_string, Styles};
 
 #[derive(Debug)]
 pub struct State {
     pub current_sheet: String,
     pub last_sheet: String,
+    pub binding: Option<Binding>,
     pub database: Connection,
+    // The post-hooks to run once the changes are committed
+    pub pending_hooks: Vec<PendingHook>,
+    pub hook_output: HookOutput,
 }
 
 impl State {
     pub fn build(config: &Config) -> Result<State> {
         let db = connect_to_db(config)?;
 
-        let proj_dirs = ProjectDirs::from("com", "andreadev-it", "timetrack")
-            .ok_or(anyhow!("Cannot get project directories for this OS."))?;
-
         // Prepare the default state
         let mut state = State {
             current_sheet: "default".to_string(),
             last_sheet: "default".to_string(),
+            binding: find_binding(),
             database: db,
+            pending_hooks: Vec::new(),
+            hook_output: HookOutput::Inherit,
         };
 
-        // Get the data file path
-        let data_dir = proj_dirs.data_local_dir();
-        let mut data_file = data_dir.to_path_buf();
-        data_file.push("data.txt");
-
-        // Extract the data from the file
-        let content_res = fs::read_to_string(&data_file);
-
-        match content_res {
-            Ok(content) => {
-                let mut lines = content.lines();
-
-                if let Some(s) = lines.next() {
-                    state.current_sheet = s.to_string();
-                }
-
-                if let Some(s) = lines.next() {
-                    state.last_sheet = s.to_string();
-                }
+        match read_sheet_state(&state.database)? {
+            Some((current_sheet, last_sheet)) => {
+                state.current_sheet = current_sheet;
+                state.last_sheet = last_sheet;
             }
-            Err(_) => {
-                // write the default file
+            None => {
                 state
-                    .update_file()
-                    .context("Cannot write the default state file.")?;
+                    .migrate_data_file()
+                    .context("Cannot migrate the old state file.")?;
             }
         }
 
         Ok(state)
     }
 
+    // The sheet bound to the working directory takes precedence over the global one
+    pub fn active_sheet(&self) -> &str {
+        match &self.binding {
+            Some(binding) => &binding.sheet,
+            None => &self.current_sheet,
+        }
+    }
+
     pub fn change_sheet(&mut self, sheet: &str) -> Result<()> {
         self.last_sheet = self.current_sheet.clone();
         self.current_sheet = sheet.to_string();
 
-        self.update_file()?;
+        self.save()?;
 
         Ok(())
     }
@@ -70,12 +69,27 @@ impl State {
     pub fn update_sheet_name(&mut self, sheet: &str) -> Result<()> {
         self.current_sheet = sheet.to_string();
 
-        self.update_file()?;
+        self.save()?;
+
+        Ok(())
+    }
+
+    pub fn update_last_sheet(&mut self, sheet: &str) -> Result<()> {
+        self.last_sheet = sheet.to_string();
+
+        self.save()?;
 
         Ok(())
     }
 
-    fn update_file(&mut self) -> Result<()> {
+    fn save(&self) -> Result<()> {
+        write_sheet_state(&self.current_sheet, &self.last_sheet, &self.database)
+    }
+
+    // Older versions kept the state in a data.txt file, next to the database.
+    // Its content is moved into the database, or the defaults are saved if it
+    // doesn't exist.
+    fn migrate_data_file(&mut self) -> Result<()> {
         let proj_dirs = ProjectDirs::from("com", "andreadev-it", "timetrack")
             .ok_or(anyhow!("Cannot get project directories for this OS."))?;
 
@@ -84,11 +98,37 @@ impl State {
         let mut data_file = data_dir.to_path_buf();
         data_file.push("data.txt");
 
-        fs::write(
-            &data_file,
-            format!("{}\n{}", self.current_sheet, self.last_sheet),
-        )?;
+        if let Ok(content) = fs::read_to_string(&data_file) {
+            let mut lines = content.lines();
+
+            if let Some(s) = lines.next() {
+                self.current_sheet = s.to_string();
+            }
+
+            if let Some(s) = lines.next() {
+                self.last_sheet = s.to_string();
+            }
+        }
+
+        self.save()?;
+
+        // The old file is kept as a backup, but it must not be migrated again
+        if data_file.exists() {
+            fs::rename(&data_file, data_file.with_extension("txt.bak"))?;
+        }
 
         Ok(())
     }
 }
+
+// A broken binding file must not stop every command, so it is only reported.
+// The warning goes to stderr, to keep the JSON and status outputs readable.
+fn find_binding() -> Option<Binding> {
+    match Binding::find() {
+        Ok(binding) => binding,
+        Err(e) => {
+            eprintln!("{} {:#}. It is ignored.", style_string("Warning:", Styles::Error), e);
+            None
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use chrono::Duration;
+
+// A task started often, e.g. with `t in @standup`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    pub note: String,
+    pub sheet: Option<String>,
+    pub tags: Vec<String>,
+    pub duration: Option<Duration>,
+}
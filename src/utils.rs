@@ -1,5 +1,8 @@
 use anyhow::Result;
-use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDateTime, TimeZone};
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
+use std::collections::BTreeMap;
+
+use crate::Entry;
 
 pub fn str_to_datetime(s: &str) -> Result<DateTime<Local>> {
     let no_tz = s.replace("+00:00", "");
@@ -23,6 +26,40 @@ pub fn format_duration(d: &Duration) -> String {
     format!("{}:{:0>2}:{:0>2}", hours, minutes, seconds)
 }
 
+// Durations are written as a sequence of amounts and units, e.g. "1h30m", "15m" or "30d"
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let mut total = Duration::zero();
+    let mut amount = String::new();
+
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            amount.push(c);
+            continue;
+        }
+
+        let value = amount
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("Invalid duration: {}", s))?;
+
+        total = total
+            + match c {
+                'd' => Duration::days(value),
+                'h' => Duration::hours(value),
+                'm' => Duration::minutes(value),
+                's' => Duration::seconds(value),
+                _ => return Err(anyhow::anyhow!("Invalid duration unit '{}' in: {}", c, s)),
+            };
+
+        amount.clear();
+    }
+
+    if !amount.is_empty() || total.is_zero() {
+        return Err(anyhow::anyhow!("Invalid duration: {}", s));
+    }
+
+    Ok(total)
+}
+
 pub fn get_month_boundaries(month: &str) -> Result<(DateTime<Local>, DateTime<Local>)> {
     let start = get_month_from_string(month)?;
     let end = get_last_day_of_month(start)?;
@@ -32,28 +69,21 @@ pub fn get_month_boundaries(month: &str) -> Result<(DateTime<Local>, DateTime<Lo
 
 // The month is written as 2024-01
 pub fn get_month_from_string(month_str: &str) -> Result<DateTime<Local>> {
-    let year = month_str.split('-').next().unwrap().parse::<i32>().unwrap();
-    let month = month_str.split('-').nth(1).unwrap().parse::<u32>().unwrap();
+    let invalid = || anyhow::anyhow!("Invalid month: {}. The format is yyyy-mm", month_str);
+
+    let (year, month) = month_str.trim().split_once('-').ok_or_else(invalid)?;
+    let year = year.parse::<i32>().map_err(|_| invalid())?;
+    let month = month.parse::<u32>().map_err(|_| invalid())?;
 
     let res = Local.with_ymd_and_hms(year, month, 1, 0, 0, 0);
 
     match res {
-        chrono::LocalResult::None => Err(anyhow::anyhow!("Invalid month")),
+        chrono::LocalResult::None => Err(invalid()),
         chrono::LocalResult::Single(dt) => Ok(dt),
         chrono::LocalResult::Ambiguous(_, _) => Err(anyhow::anyhow!("Ambiguous month")),
     }
 }
 
-pub fn get_first_day_of_month(dt: DateTime<Local>) -> Result<DateTime<Local>> {
-    let res = Local.with_ymd_and_hms(dt.year(), dt.month(), 1, 0, 0, 0);
-
-    match res {
-        LocalResult::None => Err(anyhow::anyhow!("Invalid month")),
-        LocalResult::Single(dt) => Ok(dt),
-        LocalResult::Ambiguous(_, _) => Err(anyhow::anyhow!("Ambiguous month")),
-    }
-}
-
 pub fn get_last_day_of_month(dt: DateTime<Local>) -> Result<DateTime<Local>> {
     let mut month = dt.month() + 1;
     let mut year = dt.year();
@@ -72,6 +102,21 @@ pub fn get_last_day_of_month(dt: DateTime<Local>) -> Result<DateTime<Local>> {
     }
 }
 
+// The local time at a time of the day. When the clocks are turned back, the first of the
+// two is used, and when they skip it, like midnight in some countries, the end of the gap.
+pub fn local_datetime(naive: NaiveDateTime) -> DateTime<Local> {
+    // A skipped time may come with the offset from before the gap, which shows it in the
+    // gap: the offset is looked up again from the instant
+    if let Some(dt) = Local.from_local_datetime(&naive).earliest() {
+        return Local.from_utc_datetime(&dt.naive_utc());
+    }
+
+    // The gaps last an hour or so, and end on a whole minute
+    (1..=24 * 60)
+        .find_map(|m| Local.from_local_datetime(&(naive + Duration::minutes(m))).earliest())
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
 pub fn day_begin(dt: DateTime<Local>) -> DateTime<Local> {
     Local
         .with_ymd_and_hms(dt.year(), dt.month(), dt.day(), 0, 0, 0)
@@ -88,6 +133,31 @@ pub fn is_same_day(dt1: &DateTime<Local>, dt2: &DateTime<Local>) -> bool {
     dt1.year() == dt2.year() && dt1.month() == dt2.month() && dt1.day() == dt2.day()
 }
 
+// Asks the user for a line of text, without the surrounding whitespace
+pub fn ask(msg: &str) -> String {
+    println!("{} ", msg);
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+// The time tracked on each day, counting every task on the day it started
+pub fn duration_by_day(entries: &[Entry]) -> BTreeMap<NaiveDate, Duration> {
+    let mut days = BTreeMap::new();
+
+    for entry in entries {
+        let total = days.entry(entry.start.date_naive()).or_insert(Duration::zero());
+        *total = *total + entry.get_duration();
+    }
+
+    days
+}
+
+// Hours as a decimal number, e.g. "7.50", for the summaries
+pub fn format_hours(d: &Duration) -> String {
+    format!("{:.2}", d.num_seconds() as f64 / 3600.0)
+}
+
 pub fn confirm_action(msg: &str) -> bool {
     println!("{} ", msg);
     let mut input = String::new();
@@ -95,6 +165,7 @@ pub fn confirm_action(msg: &str) -> bool {
     input.trim().to_lowercase() == "y"
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -124,4 +195,31 @@ mod tests {
 
         assert!(res.year() == 2023 && res.month() == 2 && res.day() == 28);
     }
+
+    #[test]
+    fn test_get_month_from_string() {
+        let res = get_month_from_string("2024-03").unwrap();
+        assert!(res.year() == 2024 && res.month() == 3 && res.day() == 1);
+
+        assert!(get_month_from_string("2024").is_err());
+        assert!(get_month_from_string("2024-13").is_err());
+        assert!(get_month_from_string("march").is_err());
+        assert!(get_month_from_string("").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_duration("8h").unwrap(), Duration::hours(8));
+        assert_eq!(parse_duration("30d").unwrap(), Duration::days(30));
+        assert_eq!(
+            parse_duration("1h30m").unwrap(),
+            Duration::hours(1) + Duration::minutes(30)
+        );
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("15").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("2w").is_err());
+    }
 }
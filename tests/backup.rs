@@ -0,0 +1,53 @@
+mod common;
+
+use common::{t, temp_home};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+fn run(home: &Path, args: &[&str]) -> String {
+    let output = t(home).args(args).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn notes(home: &Path) -> Vec<String> {
+    let output = run(home, &["display", "--json"]);
+    let entries: Value = serde_json::from_str(&output).unwrap();
+
+    entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_backup_and_restore() {
+    let home = temp_home();
+    let backup = home.join("backup.db");
+
+    run(&home, &["in", "pdf exporter"]);
+    run(&home, &["out"]);
+    run(&home, &["backup", backup.to_str().unwrap()]);
+
+    run(&home, &["in", "after the backup"]);
+    assert_eq!(notes(&home), vec!["pdf exporter", "after the backup"]);
+
+    run(&home, &["restore", backup.to_str().unwrap()]);
+    assert_eq!(notes(&home), vec!["pdf exporter"]);
+
+    // The full-text index came back too
+    let found = run(&home, &["search", "exporter"]);
+    assert!(found.contains("pdf exporter"));
+
+    // Files that are not databases are refused
+    let broken = home.join("broken.db");
+    fs::write(&broken, "not a database").unwrap();
+
+    let output = t(&home).args(["restore", broken.to_str().unwrap()]).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(notes(&home), vec!["pdf exporter"]);
+}
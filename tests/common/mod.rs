@@ -0,0 +1,27 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// An empty directory for the data and the configuration of one test
+pub fn temp_home() -> PathBuf {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    let name = format!("timetrack-test-{}-{}", std::process::id(), nanos);
+    let home = env::temp_dir().join(name);
+    fs::create_dir_all(&home).unwrap();
+
+    home
+}
+
+// The program, with its data and configuration kept in the given directory
+pub fn t(home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_t"));
+    command
+        .env("HOME", home)
+        .env("XDG_DATA_HOME", home.join("data"))
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .current_dir(home);
+
+    command
+}
@@ -0,0 +1,151 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{t, temp_home};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// Writes the configuration with the given hooks, in the directory used by `t`
+fn configure(home: &Path, hooks: Value) {
+    let dir = home.join("config").join("timetrack");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("config.json"), json!({ "hooks": hooks }).to_string()).unwrap();
+}
+
+fn run(home: &Path, args: &[&str]) -> Output {
+    t(home).args(args).output().unwrap()
+}
+
+#[test]
+fn test_post_hooks_receive_the_entry() {
+    let home = temp_home();
+    configure(
+        &home,
+        json!({
+            "post-in": "cat > post-in.json",
+            "post-out": "echo \"$TIMETRACK_HOOK $TIMETRACK_ID $TIMETRACK_SHEET\" > post-out.txt",
+        }),
+    );
+
+    assert!(run(&home, &["in", "review", "--tag", "code"]).status.success());
+    assert!(run(&home, &["out"]).status.success());
+
+    let entry: Value =
+        serde_json::from_str(&fs::read_to_string(home.join("post-in.json")).unwrap()).unwrap();
+    assert_eq!(entry["id"], 1);
+    assert_eq!(entry["name"], "review");
+    assert_eq!(entry["tags"], json!(["code"]));
+
+    let line = fs::read_to_string(home.join("post-out.txt")).unwrap();
+    assert_eq!(line.trim(), "post-out 1 default");
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn test_switch_runs_the_out_hooks() {
+    let home = temp_home();
+    let log = "echo \"$TIMETRACK_HOOK $TIMETRACK_ID ${TIMETRACK_END:+stopped}\" >> hooks.log";
+    configure(&home, json!({ "pre-out": log, "post-out": log, "post-in": log }));
+
+    assert!(run(&home, &["in", "first"]).status.success());
+    assert!(run(&home, &["in", "second", "--switch"]).status.success());
+
+    let lines = fs::read_to_string(home.join("hooks.log")).unwrap();
+    let lines: Vec<&str> = lines.lines().map(|l| l.trim()).collect();
+    assert_eq!(lines, ["post-in 1", "pre-out 1 stopped", "post-out 1 stopped", "post-in 2"]);
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn test_failing_pre_hook_aborts() {
+    let home = temp_home();
+    configure(&home, json!({ "pre-in": "test \"$TIMETRACK_NOTE\" != blocked" }));
+
+    let output = run(&home, &["in", "blocked"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("pre-in hook"));
+
+    let output = run(&home, &["current"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("blocked"));
+
+    // Unless the hooks are skipped
+    assert!(run(&home, &["in", "blocked", "--no-hooks"]).status.success());
+
+    let output = run(&home, &["current"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("blocked"));
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn test_pre_hooks_run_before_the_lock() {
+    let home = temp_home();
+    configure(&home, json!({ "pre-in": "touch started; sleep 2; touch done" }));
+
+    let slow = t(&home).args(["in", "slow"]).stdout(Stdio::piped()).spawn().unwrap();
+    while !home.join("started").exists() {
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    // Another command can change the data while the hook is running
+    assert!(run(&home, &["sheet", "other"]).status.success());
+    assert!(!home.join("done").exists());
+
+    // And the slow command notices it, instead of overwriting the change
+    let output = slow.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("another timetrack command"));
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn test_rpc_runs_the_hooks() {
+    let home = temp_home();
+    let log = "echo noise; echo \"$TIMETRACK_HOOK $TIMETRACK_ID\" >> hooks.log";
+    configure(
+        &home,
+        json!({ "pre-in": "test \"$TIMETRACK_NOTE\" != blocked", "post-in": log, "post-out": log }),
+    );
+
+    let mut child = t(&home)
+        .arg("rpc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let requests = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "start", "params": { "name": "review" } }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "start", "params": { "name": "blocked" } }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "stop" }),
+    ];
+    let mut stdin = child.stdin.take().unwrap();
+    for request in requests {
+        writeln!(stdin, "{}", request).unwrap();
+    }
+    drop(stdin);
+
+    // The output of the hooks doesn't get mixed with the responses
+    let output = child.wait_with_output().unwrap();
+    let responses: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[1]["error"]["code"], -32002);
+
+    let lines = fs::read_to_string(home.join("hooks.log")).unwrap();
+    assert_eq!(lines.lines().collect::<Vec<_>>(), ["post-in 1", "post-out 1"]);
+
+    let _ = fs::remove_dir_all(&home);
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::{t, temp_home};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Stdio};
+
+// `t rpc` running on its own empty database, stopped when dropped
+struct TestClient {
+    home: PathBuf,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+}
+
+impl TestClient {
+    fn start() -> TestClient {
+        let home = temp_home();
+
+        let mut child = t(&home)
+            .arg("rpc")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+
+        TestClient {
+            home,
+            child,
+            stdin,
+            stdout,
+            next_id: 1,
+        }
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        self.send(&request.to_string());
+
+        let response = self.receive();
+        assert_eq!(response["id"], id);
+
+        response
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.stdin, "{}", line).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let line = self.stdout.next().unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
+
+#[test]
+fn test_start_and_stop() {
+    let mut client = TestClient::start();
+
+    let response = client.call("start", json!({ "name": "first", "tags": ["review"] }));
+    let entry = &response["result"];
+    assert_eq!(entry["sheet"], "default");
+    assert_eq!(entry["tags"], json!(["review"]));
+
+    let response = client.call("current", Value::Null);
+    assert_eq!(response["result"]["entry"]["id"], entry["id"]);
+
+    // Like `t in`, a second task needs a switch
+    let response = client.call("start", json!({ "name": "second" }));
+    assert_eq!(response["error"]["code"], -32002);
+
+    let update = json!({ "id": entry["id"], "name": "renamed", "start": "not a date" });
+    let response = client.call("entries.update", update);
+    assert_eq!(response["error"]["code"], -32602);
+
+    let response = client.call("entries.update", json!({ "id": entry["id"], "name": "renamed" }));
+    assert_eq!(response["result"]["name"], "renamed");
+
+    let response = client.call("stop", Value::Null);
+    assert!(response["result"]["end"].is_string());
+
+    let response = client.call("entries.query", json!({ "range": "today" }));
+    assert_eq!(response["result"].as_array().unwrap().len(), 1);
+
+    let response = client.call("list", Value::Null);
+    assert_eq!(response["result"][0]["name"], "default");
+}
+
+#[test]
+fn test_invalid_requests() {
+    let mut client = TestClient::start();
+
+    client.send("not json");
+    assert_eq!(client.receive()["error"]["code"], -32700);
+
+    // A line that isn't UTF-8 doesn't stop the server
+    client.stdin.write_all(b"\xff\n").unwrap();
+    client.stdin.flush().unwrap();
+    let response = client.receive();
+    assert_eq!(response["error"]["code"], -32700);
+    assert!(response["id"].is_null());
+
+    client.send(r#"{ "id": 1, "method": "list" }"#);
+    assert_eq!(client.receive()["error"]["code"], -32600);
+
+    let response = client.call("unknown", Value::Null);
+    assert_eq!(response["error"]["code"], -32601);
+
+    // Notifications get no response, so the next line answers the next call
+    client.send(r#"{ "jsonrpc": "2.0", "method": "list" }"#);
+    let response = client.call("current", Value::Null);
+    assert_eq!(response["result"]["sheet"], "default");
+}
+
+#[test]
+fn test_notifies_changes_from_other_processes() {
+    let mut client = TestClient::start();
+
+    // The database exists once the first call has been answered
+    client.call("list", Value::Null);
+
+    let status = t(&client.home)
+        .args(["in", "elsewhere"])
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let notification = client.receive();
+    assert_eq!(notification["method"], "running.changed");
+    assert_eq!(notification["params"]["running"][0]["name"], "elsewhere");
+}
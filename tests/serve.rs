@@ -0,0 +1,226 @@
+mod common;
+
+use common::{t, temp_home};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+
+const TOKEN: &str = "test-token";
+
+// A server running on its own empty database, stopped when dropped
+struct TestServer {
+    home: PathBuf,
+    port: u16,
+    child: Child,
+}
+
+impl TestServer {
+    fn start() -> TestServer {
+        let home = temp_home();
+
+        // Any free port will do
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+        let mut child = t(&home)
+            .args(["serve", "--port", &port.to_string(), "--token", TOKEN])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        // The server is ready once it says where it is listening
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        assert!(line.contains("Listening on"), "unexpected output: {}", line);
+
+        TestServer { home, port, child }
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+        self.request_with_token(method, path, body, Some(TOKEN))
+    }
+
+    fn request_with_token(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<Value>,
+        token: Option<&str>,
+    ) -> (u16, Value) {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {}\r\n", t))
+            .unwrap_or_default();
+        let headers = format!("Host: localhost\r\nContent-Type: application/json\r\n{}", auth);
+
+        self.send(method, path, &headers, &body)
+    }
+
+    // The headers end with "\r\n", the connection and length headers are added
+    fn send(&self, method: &str, path: &str, headers: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\n{}Connection: close\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            headers,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+
+        (status, serde_json::from_str(body).unwrap())
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
+
+#[test]
+fn test_requires_token() {
+    let server = TestServer::start();
+
+    let (status, _) = server.request_with_token("GET", "/sheets", None, None);
+    assert_eq!(status, 401);
+
+    let (status, _) = server.request_with_token("GET", "/sheets", None, Some("wrong"));
+    assert_eq!(status, 401);
+
+    let (status, body) = server.request("GET", "/sheets", None);
+    assert_eq!(status, 200);
+    assert_eq!(body, json!([]));
+}
+
+#[test]
+fn test_requires_local_host_and_json() {
+    let server = TestServer::start();
+    let auth = format!("Authorization: Bearer {}\r\n", TOKEN);
+    let body = json!({ "name": "first" }).to_string();
+
+    // A page reaching the server through DNS rebinding still names its own site
+    let headers = format!("Host: evil.example\r\nContent-Type: application/json\r\n{}", auth);
+    let (status, _) = server.send("POST", "/in", &headers, &body);
+    assert_eq!(status, 403);
+
+    let headers = format!("Host: localhost:{}\r\n{}", server.port, auth);
+    let (status, _) = server.send("GET", "/sheets", &headers, "");
+    assert_eq!(status, 200);
+
+    // Forms can only send text or form data
+    let headers = format!("Host: localhost\r\nContent-Type: text/plain\r\n{}", auth);
+    let (status, _) = server.send("POST", "/in", &headers, &body);
+    assert_eq!(status, 415);
+
+    let headers = format!("Host: localhost\r\n{}", auth);
+    let (status, _) = server.send("POST", "/out", &headers, "");
+    assert_eq!(status, 415);
+
+    // Nothing was written by the refused requests
+    let (_, current) = server.request("GET", "/current", None);
+    assert!(current["entry"].is_null(), "{}", current);
+}
+
+#[test]
+fn test_check_in_and_out() {
+    let server = TestServer::start();
+
+    let (status, entry) = server.request("POST", "/in", Some(json!({ "name": "first" })));
+    assert_eq!(status, 201);
+    assert_eq!(entry["sheet"], "default");
+    assert_eq!(entry["end"], Value::Null);
+
+    let (_, current) = server.request("GET", "/current", None);
+    assert_eq!(current["entry"]["id"], entry["id"]);
+
+    // Like `t in`, a second task needs a switch
+    let (status, _) = server.request("POST", "/in", Some(json!({ "name": "second" })));
+    assert_eq!(status, 409);
+
+    let body = json!({ "name": "second", "switch": true, "tags": ["review"] });
+    let (status, second) = server.request("POST", "/in", Some(body));
+    assert_eq!(status, 201);
+    assert_eq!(second["tags"], json!(["review"]));
+
+    let (status, stopped) = server.request("POST", "/out", None);
+    assert_eq!(status, 200);
+    assert_eq!(stopped["id"], second["id"]);
+
+    let (status, _) = server.request("POST", "/out", None);
+    assert_eq!(status, 409);
+
+    let (_, entries) = server.request("GET", "/entries?range=today", None);
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+    assert!(entries[0]["end"].is_string());
+
+    let (_, sheets) = server.request("GET", "/sheets", None);
+    assert_eq!(sheets[0]["name"], "default");
+    assert_eq!(sheets[0]["running"], false);
+}
+
+#[test]
+fn test_edit_and_delete_entries() {
+    let server = TestServer::start();
+
+    let body = json!({ "name": "draft", "at": "2024-03-01T09:00:00Z" });
+    let (_, entry) = server.request("POST", "/in", Some(body));
+    server.request("POST", "/out", Some(json!({ "at": "2024-03-01T10:00:00Z" })));
+
+    let path = format!("/entries/{}", entry["id"]);
+    let body = json!({ "name": "final", "sheet": "client", "end": "2024-03-01T11:00:00Z" });
+    let (status, edited) = server.request("PATCH", &path, Some(body));
+    assert_eq!(status, 200);
+    assert_eq!(edited["name"], "final");
+
+    let query = "/entries?sheet=client&start=2024-03-01T00%3A00%3A00Z";
+    let (_, entries) = server.request("GET", query, None);
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+    assert_eq!(entries[0]["end"], "2024-03-01T11:00:00Z");
+
+    let (status, _) = server.request("PATCH", &path, Some(json!({ "start": "not a date" })));
+    assert_eq!(status, 400);
+
+    let (status, _) = server.request("DELETE", &path, None);
+    assert_eq!(status, 200);
+
+    let (status, _) = server.request("DELETE", &path, None);
+    assert_eq!(status, 404);
+
+    let (_, entries) = server.request("GET", "/entries?sheet=client", None);
+    assert_eq!(entries, json!([]));
+}
+
+#[test]
+fn test_archived_sheets_are_read_only() {
+    let server = TestServer::start();
+
+    let body = json!({ "sheet": "old", "at": "2024-03-01T09:00:00Z" });
+    server.request("POST", "/in", Some(body));
+    server.request("POST", "/out", Some(json!({ "sheet": "old" })));
+
+    let status = t(&server.home)
+        .args(["sheet", "old", "--archive"])
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let (status, body) = server.request("POST", "/in", Some(json!({ "sheet": "old" })));
+    assert_eq!(status, 409);
+    assert_eq!(body["error"], "The sheet is archived: old");
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{t, temp_home};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+fn run(home: &Path, args: &[&str]) -> String {
+    let output = t(home).args(args).output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn entries(home: &Path, sheet: &str) -> Vec<Value> {
+    let output = run(home, &["display", "--json", sheet]);
+    let entries: Value = serde_json::from_str(&output).unwrap();
+
+    entries.as_array().unwrap().clone()
+}
+
+#[test]
+fn test_start_and_log_templates() {
+    let home = temp_home();
+
+    run(
+        &home,
+        &[
+            "template", "add", "standup", "--sheet", "team", "--note", "Daily standup", "--tag",
+            "meeting", "--duration", "15m",
+        ],
+    );
+
+    run(&home, &["in", "@standup", "--tag", "extra"]);
+    run(&home, &["out", "--sheet", "team"]);
+    run(&home, &["log", "@standup", "--at", "2024-03-01 09:30"]);
+
+    let entries = entries(&home, "team");
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e["name"] == "Daily standup"));
+
+    let logged = entries.iter().find(|e| e["tags"].as_array().unwrap().len() == 1).unwrap();
+    assert_eq!(logged["tags"][0], "meeting");
+
+    let start = logged["start"].as_str().unwrap();
+    let end = logged["end"].as_str().unwrap();
+    assert!(start.contains("09:30:00"));
+    assert!(end.contains("09:45:00"));
+
+    // Unknown templates start nothing
+    assert!(run(&home, &["in", "@unknown"]).contains("Template not found"));
+    assert!(!run(&home, &["current"]).contains("unknown"));
+
+    let _ = fs::remove_dir_all(&home);
+}
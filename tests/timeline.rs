@@ -0,0 +1,25 @@
+mod common;
+
+use common::{t, temp_home};
+use std::fs;
+
+// In Havana, the clocks skipped from midnight to one o'clock on that day
+#[test]
+fn test_day_starting_after_a_dst_gap() {
+    let home = temp_home();
+    let run = |args: &[&str]| {
+        let output = t(&home).env("TZ", "America/Havana").args(args).output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    run(&["in", "night shift", "--at", "2024-03-09 22:00"]);
+    run(&["out", "--at", "2024-03-10 10:00"]);
+
+    let output = run(&["timeline", "--day", "2024-03-10", "--full"]);
+    let ruler = output.lines().nth(1).unwrap();
+    assert!(ruler.trim_start().starts_with("01 02"), "{}", output);
+
+    let _ = fs::remove_dir_all(&home);
+}